@@ -1,66 +1,462 @@
+use crate::config::{self, AiProviderKind, AiSettings};
 use crate::MyError;
-use async_openai::types::{ChatCompletionRequestMessage, CreateChatCompletionRequestArgs, Role};
+use async_openai::types::{
+    ChatCompletionRequestMessage, CreateChatCompletionRequest, CreateChatCompletionRequestArgs,
+    Role,
+};
 use async_openai::Client;
+use futures::StreamExt;
+use std::collections::{HashMap, VecDeque};
 use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-/// Blocking function to get a summary via OpenAI.
-pub fn openai_summarize_blocking(content: String) -> Result<String, MyError> {
-    let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(async move {
-        let key = env::var("OPENAI_API_KEY").map_err(|_| "Please set OPENAI_API_KEY!")?;
-        let cli = Client::new().with_api_key(key);
-        let req = CreateChatCompletionRequestArgs::default()
-            .model("gpt-4o-latest")
-            .messages(vec![
-                ChatCompletionRequestMessage {
-                    role: Role::System,
-                    content: "You are a helpful assistant that summarizes notes.".to_string(),
-                    name: None,
-                },
-                ChatCompletionRequestMessage {
-                    role: Role::User,
-                    content: format!("Summarize:\n\n{}", content),
-                    name: None,
-                },
-            ])
-            .build()?;
-        let resp = cli.chat().create(req).await?;
-        let txt = resp
-            .choices
-            .first()
-            .map(|c| c.message.content.clone())
-            .unwrap_or_else(|| "No summary received.".to_string());
-        Ok(txt)
-    })
-}
-
-/// Blocking function to extract keywords via OpenAI.
-pub fn openai_keywords_blocking(content: String) -> Result<String, MyError> {
-    let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(async move {
-        let key = env::var("OPENAI_API_KEY").map_err(|_| "Please set OPENAI_API_KEY!")?;
-        let cli = Client::new().with_api_key(key);
-        let req = CreateChatCompletionRequestArgs::default()
-            .model("gpt-4o-latest")
+/// Model used when the config names none.
+pub const DEFAULT_MODEL: &str = "gpt-4o-latest";
+
+/// Environment variable holding the API key when the config names none.
+pub const DEFAULT_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Pause between the words streamed by [`MockProvider`].
+const MOCK_CHUNK_DELAY: Duration = Duration::from_millis(25);
+
+/// Something the AI can do with a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiTask {
+    Summarize,
+    Keywords,
+}
+
+impl AiTask {
+    pub const ALL: [AiTask; 2] = [AiTask::Summarize, AiTask::Keywords];
+
+    /// Name used in commands, URLs and the `ai.models` config.
+    pub fn name(self) -> &'static str {
+        match self {
+            AiTask::Summarize => "summarize",
+            AiTask::Keywords => "keywords",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<AiTask> {
+        AiTask::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    fn system_prompt(self) -> &'static str {
+        match self {
+            AiTask::Summarize => "You are a helpful assistant that summarizes notes.",
+            AiTask::Keywords => "You are a helpful assistant that extracts keywords.",
+        }
+    }
+
+    fn user_prompt(self, content: &str) -> String {
+        match self {
+            AiTask::Summarize => format!("Summarize:\n\n{}", content),
+            AiTask::Keywords => format!("Extract keywords:\n\n{}", content),
+        }
+    }
+}
+
+/// A backend that runs AI tasks on note content.
+pub trait AiProvider: Send + Sync {
+    /// Short name shown in status messages and API responses.
+    fn name(&self) -> &str;
+
+    /// The model used for `task`.
+    fn model(&self, task: AiTask) -> String;
+
+    /// Run `task` on `content` and return the model's answer. Blocks until
+    /// the answer is complete, or fails soon after `cancel` is set.
+    fn complete(&self, task: AiTask, content: &str, cancel: &AtomicBool)
+        -> Result<String, MyError>;
+
+    /// Like [`AiProvider::complete`], but hands the answer to `on_chunk`
+    /// piece by piece as it arrives. Returns the whole answer. Providers
+    /// that can't stream pass it in one piece.
+    fn stream(
+        &self,
+        task: AiTask,
+        content: &str,
+        cancel: &AtomicBool,
+        on_chunk: &mut dyn FnMut(&str),
+    ) -> Result<String, MyError> {
+        let text = self.complete(task, content, cancel)?;
+        on_chunk(&text);
+        Ok(text)
+    }
+}
+
+/// Resolves once `cancel` is set.
+async fn cancelled(cancel: &AtomicBool) {
+    while !cancel.load(Ordering::Relaxed) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Any server speaking the OpenAI chat completions API: OpenAI itself, or a
+/// local llama.cpp or Ollama server via `base_url`.
+pub struct OpenAiProvider {
+    base_url: Option<String>,
+    api_key_env: String,
+    model: String,
+    models: HashMap<String, String>,
+}
+
+impl OpenAiProvider {
+    pub fn new(settings: &AiSettings) -> Self {
+        OpenAiProvider {
+            base_url: settings.base_url.clone(),
+            api_key_env: settings
+                .api_key_env
+                .clone()
+                .unwrap_or_else(|| DEFAULT_API_KEY_ENV.to_string()),
+            model: settings
+                .model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            models: settings.models.clone(),
+        }
+    }
+
+    /// The chat request for `task` on `content`.
+    fn request(&self, task: AiTask, content: &str) -> Result<CreateChatCompletionRequest, MyError> {
+        Ok(CreateChatCompletionRequestArgs::default()
+            .model(self.model(task))
             .messages(vec![
                 ChatCompletionRequestMessage {
                     role: Role::System,
-                    content: "You are a helpful assistant that extracts keywords.".to_string(),
+                    content: task.system_prompt().to_string(),
                     name: None,
                 },
                 ChatCompletionRequestMessage {
                     role: Role::User,
-                    content: format!("Extract keywords:\n\n{}", content),
+                    content: task.user_prompt(content),
                     name: None,
                 },
             ])
-            .build()?;
-        let resp = cli.chat().create(req).await?;
-        let txt = resp
+            .build()?)
+    }
+
+    /// A client for the configured server. Local servers usually need no
+    /// key, so a missing key is only an error for the default endpoint.
+    fn client(&self) -> Result<Client, MyError> {
+        let key = match (env::var(&self.api_key_env), &self.base_url) {
+            (Ok(key), _) => key,
+            (Err(_), Some(_)) => String::new(),
+            (Err(_), None) => return Err(format!("Please set {}!", self.api_key_env).into()),
+        };
+        let client = Client::new().with_api_key(key);
+        Ok(match &self.base_url {
+            Some(url) => client.with_api_base(url.trim_end_matches('/')),
+            None => client,
+        })
+    }
+}
+
+impl AiProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn model(&self, task: AiTask) -> String {
+        self.models
+            .get(task.name())
+            .cloned()
+            .unwrap_or_else(|| self.model.clone())
+    }
+
+    fn complete(
+        &self,
+        task: AiTask,
+        content: &str,
+        cancel: &AtomicBool,
+    ) -> Result<String, MyError> {
+        let cli = self.client()?;
+        let req = self.request(task, content)?;
+        let rt = tokio::runtime::Runtime::new()?;
+        let chat = cli.chat();
+        let resp = rt.block_on(async {
+            tokio::select! {
+                resp = chat.create(req) => resp.map_err(MyError::from),
+                _ = cancelled(cancel) => Err("Cancelled".into()),
+            }
+        })?;
+        Ok(resp
             .choices
             .first()
             .map(|c| c.message.content.clone())
-            .unwrap_or_else(|| "No keywords received.".to_string());
-        Ok(txt)
-    })
+            .unwrap_or_else(|| format!("No {} received.", task.name())))
+    }
+
+    fn stream(
+        &self,
+        task: AiTask,
+        content: &str,
+        cancel: &AtomicBool,
+        on_chunk: &mut dyn FnMut(&str),
+    ) -> Result<String, MyError> {
+        let cli = self.client()?;
+        let req = self.request(task, content)?;
+        let rt = tokio::runtime::Runtime::new()?;
+        let chat = cli.chat();
+        rt.block_on(async {
+            let mut stream = tokio::select! {
+                stream = chat.create_stream(req) => stream?,
+                _ = cancelled(cancel) => return Err("Cancelled".into()),
+            };
+            let mut out = String::new();
+            loop {
+                let item = tokio::select! {
+                    item = stream.next() => item,
+                    _ = cancelled(cancel) => return Err("Cancelled".into()),
+                };
+                let Some(item) = item else {
+                    break;
+                };
+                for choice in item?.choices {
+                    if let Some(text) = choice.delta.content {
+                        on_chunk(&text);
+                        out.push_str(&text);
+                    }
+                }
+            }
+            Ok(out)
+        })
+    }
+}
+
+/// Answers from the note text alone, without a network: the first sentences
+/// as the summary and the most frequent words as keywords. The same note
+/// always gives the same answer.
+pub struct MockProvider;
+
+impl MockProvider {
+    /// The body text without front matter, headings or Markdown markers.
+    fn plain_words(content: &str) -> Vec<String> {
+        let (_, body) = crate::notes::split_front_matter(content);
+        body.lines()
+            .filter(|l| !l.trim_start().starts_with('#'))
+            .flat_map(|l| l.split_whitespace())
+            .map(|w| {
+                w.trim_matches(|c: char| !c.is_alphanumeric() && c != '.')
+                    .to_string()
+            })
+            .filter(|w| !w.is_empty())
+            .collect()
+    }
+}
+
+impl AiProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn model(&self, _task: AiTask) -> String {
+        "mock".to_string()
+    }
+
+    fn complete(
+        &self,
+        task: AiTask,
+        content: &str,
+        _cancel: &AtomicBool,
+    ) -> Result<String, MyError> {
+        let words = MockProvider::plain_words(content);
+        Ok(match task {
+            AiTask::Summarize => {
+                let summary: Vec<&str> = words.iter().take(30).map(String::as_str).collect();
+                if summary.is_empty() {
+                    "The note is empty.".to_string()
+                } else {
+                    summary.join(" ")
+                }
+            }
+            AiTask::Keywords => {
+                let mut counts: HashMap<String, usize> = HashMap::new();
+                for w in &words {
+                    let w = w.trim_matches('.').to_lowercase();
+                    if w.chars().count() > 3 {
+                        *counts.entry(w).or_default() += 1;
+                    }
+                }
+                let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+                ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                ranked
+                    .into_iter()
+                    .take(5)
+                    .map(|(w, _)| w)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        })
+    }
+
+    /// Streams the answer word by word, a little apart, so streaming can be
+    /// tried without a model.
+    fn stream(
+        &self,
+        task: AiTask,
+        content: &str,
+        cancel: &AtomicBool,
+        on_chunk: &mut dyn FnMut(&str),
+    ) -> Result<String, MyError> {
+        let text = self.complete(task, content, cancel)?;
+        for piece in text.split_inclusive(' ') {
+            if cancel.load(Ordering::Relaxed) {
+                return Err("Cancelled".into());
+            }
+            on_chunk(piece);
+            thread::sleep(MOCK_CHUNK_DELAY);
+        }
+        Ok(text)
+    }
+}
+
+/// The provider selected by the `ai` section of the vault config.
+pub fn provider() -> Box<dyn AiProvider> {
+    let settings = config::load_config().ai;
+    match settings.provider {
+        AiProviderKind::Openai => Box::new(OpenAiProvider::new(&settings)),
+        AiProviderKind::Mock => Box::new(MockProvider),
+    }
+}
+
+/// A request to run an AI task on a note.
+#[derive(Debug, Clone)]
+pub struct AiJob {
+    pub task: AiTask,
+    pub stem: String,
+    pub content: String,
+}
+
+/// A job that finished, failed or was cancelled.
+#[derive(Debug)]
+pub struct AiJobDone {
+    pub task: AiTask,
+    pub stem: String,
+    pub result: Result<String, String>,
+}
+
+struct RunningJob {
+    id: u64,
+    job: AiJob,
+    cancel: Arc<AtomicBool>,
+    started: Instant,
+    /// The answer streamed so far.
+    output: String,
+}
+
+/// Message from a worker thread.
+enum JobEvent {
+    Chunk(u64, String),
+    Done(u64, Result<String, String>),
+}
+
+/// AI jobs run one at a time on a worker thread, in the order queued.
+/// Results come back over a channel, collected with [`AiJobQueue::poll`].
+pub struct AiJobQueue {
+    queue: VecDeque<AiJob>,
+    running: Option<RunningJob>,
+    next_id: u64,
+    tx: Sender<JobEvent>,
+    rx: Receiver<JobEvent>,
+}
+
+impl Default for AiJobQueue {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        AiJobQueue {
+            queue: VecDeque::new(),
+            running: None,
+            next_id: 0,
+            tx,
+            rx,
+        }
+    }
+}
+
+impl AiJobQueue {
+    /// Queue a job, starting it right away if nothing is running.
+    pub fn push(&mut self, job: AiJob) {
+        self.queue.push_back(job);
+        self.start_next();
+    }
+
+    /// The running job, how long it has been running and its answer so far.
+    pub fn running(&self) -> Option<(&AiJob, Duration, &str)> {
+        self.running
+            .as_ref()
+            .map(|r| (&r.job, r.started.elapsed(), r.output.as_str()))
+    }
+
+    /// Number of jobs waiting behind the running one.
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Cancel the running job and start the next one. Returns the
+    /// cancelled job.
+    pub fn cancel(&mut self) -> Option<AiJob> {
+        let running = self.running.take()?;
+        running.cancel.store(true, Ordering::Relaxed);
+        self.start_next();
+        Some(running.job)
+    }
+
+    /// Collect what the running job has streamed, and its result once it
+    /// finishes, starting the next job. Output of cancelled jobs is dropped.
+    pub fn poll(&mut self) -> Option<AiJobDone> {
+        while let Ok(event) = self.rx.try_recv() {
+            let Some(running) = self.running.as_mut() else {
+                continue;
+            };
+            match event {
+                JobEvent::Chunk(id, text) if id == running.id => running.output.push_str(&text),
+                JobEvent::Done(id, result) if id == running.id => {
+                    let job = self.running.take()?.job;
+                    self.start_next();
+                    return Some(AiJobDone {
+                        task: job.task,
+                        stem: job.stem,
+                        result,
+                    });
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn start_next(&mut self) {
+        if self.running.is_some() {
+            return;
+        }
+        let Some(job) = self.queue.pop_front() else {
+            return;
+        };
+        self.next_id += 1;
+        let id = self.next_id;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, flag) = (self.tx.clone(), cancel.clone());
+        let (task, content) = (job.task, job.content.clone());
+        thread::spawn(move || {
+            let mut on_chunk = |text: &str| {
+                let _ = tx.send(JobEvent::Chunk(id, text.to_string()));
+            };
+            let result = provider()
+                .stream(task, &content, &flag, &mut on_chunk)
+                .map_err(|e| e.to_string());
+            let _ = tx.send(JobEvent::Done(id, result));
+        });
+        self.running = Some(RunningJob {
+            id,
+            job,
+            cancel,
+            started: Instant::now(),
+            output: String::new(),
+        });
+    }
 }
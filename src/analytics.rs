@@ -0,0 +1,384 @@
+use crate::graph::{NoteLink, NoteNode};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+/// PageRank damping factor.
+const DAMPING: f64 = 0.85;
+/// PageRank stops once the total change drops below this.
+const PAGERANK_TOLERANCE: f64 = 1e-9;
+const PAGERANK_MAX_ITER: usize = 100;
+/// Label propagation rounds before giving up on convergence.
+const COMMUNITY_MAX_ITER: usize = 100;
+
+/// Centrality scores and community of one graph node.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeMetrics {
+    pub id: String,
+    pub is_tag: bool,
+    pub degree: usize,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    /// PageRank over directed links; sums to 1 across the graph.
+    pub pagerank: f64,
+    /// Normalized betweenness centrality over undirected links, 0 to 1.
+    pub betweenness: f64,
+    /// Community index, 0 being the largest community.
+    pub community: usize,
+}
+
+/// A group of densely connected nodes found by label propagation.
+#[derive(Debug, Clone, Serialize)]
+pub struct Community {
+    pub id: usize,
+    pub size: usize,
+    /// Member node ids, tags prefixed with `#`.
+    pub members: Vec<String>,
+}
+
+/// Centrality and community results for a graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphAnalytics {
+    /// Nodes ordered by PageRank, highest first.
+    pub nodes: Vec<NodeMetrics>,
+    pub communities: Vec<Community>,
+    /// Position in `nodes` by (id, is_tag).
+    #[serde(skip)]
+    index: HashMap<(String, bool), usize>,
+}
+
+impl GraphAnalytics {
+    /// Metrics for a node.
+    pub fn get(&self, id: &str, is_tag: bool) -> Option<&NodeMetrics> {
+        let i = *self.index.get(&(id.to_string(), is_tag))?;
+        self.nodes.get(i)
+    }
+
+    /// Human-readable report: hub notes by each centrality, then communities.
+    pub fn summary(&self, limit: usize) -> String {
+        let mut out = String::new();
+        let notes: Vec<&NodeMetrics> = self.nodes.iter().filter(|n| !n.is_tag).collect();
+        let _ = writeln!(
+            out,
+            "{} notes, {} tags, {} communities",
+            notes.len(),
+            self.nodes.len() - notes.len(),
+            self.communities.len()
+        );
+
+        let mut by =
+            |title: &str, key: &dyn Fn(&NodeMetrics) -> f64, fmt: &dyn Fn(f64) -> String| {
+                let mut ranked = notes.clone();
+                ranked.sort_by(|a, b| key(b).total_cmp(&key(a)).then_with(|| a.id.cmp(&b.id)));
+                let _ = writeln!(out, "\n{}:", title);
+                for n in ranked.iter().take(limit) {
+                    let _ = writeln!(out, "  {:>8}  {}", fmt(key(n)), n.id);
+                }
+            };
+        by("Top notes by PageRank", &|n| n.pagerank, &|v| {
+            format!("{:.4}", v)
+        });
+        by("Top notes by betweenness", &|n| n.betweenness, &|v| {
+            format!("{:.4}", v)
+        });
+        by("Top notes by degree", &|n| n.degree as f64, &|v| {
+            format!("{}", v)
+        });
+
+        let _ = writeln!(out, "\nCommunities:");
+        for c in self.communities.iter().filter(|c| c.size > 1).take(limit) {
+            let shown: Vec<&str> = c.members.iter().take(8).map(String::as_str).collect();
+            let more = if c.size > shown.len() {
+                format!(", ... ({} more)", c.size - shown.len())
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                out,
+                "  {:>3} [{}]  {}{}",
+                c.id,
+                c.size,
+                shown.join(", "),
+                more
+            );
+        }
+        out
+    }
+}
+
+/// Nodes keyed by index, with directed and undirected adjacency lists.
+struct Indexed<'a> {
+    nodes: &'a [NoteNode],
+    out: Vec<Vec<usize>>,
+    undirected: Vec<Vec<usize>>,
+}
+
+impl<'a> Indexed<'a> {
+    fn new<'l>(nodes: &'a [NoteNode], links: impl IntoIterator<Item = &'l NoteLink>) -> Self {
+        let index: HashMap<(&str, bool), usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| ((n.id.as_str(), n.is_tag), i))
+            .collect();
+        let mut out = vec![Vec::new(); nodes.len()];
+        let mut undirected = vec![Vec::new(); nodes.len()];
+        for l in links {
+            let s = index.get(&(l.source.as_str(), l.kind.source_is_tag()));
+            let t = index.get(&(l.target.as_str(), l.kind.target_is_tag()));
+            if let (Some(&s), Some(&t)) = (s, t) {
+                if s == t {
+                    continue;
+                }
+                out[s].push(t);
+                undirected[s].push(t);
+                undirected[t].push(s);
+            }
+        }
+        for adj in out.iter_mut().chain(undirected.iter_mut()) {
+            adj.sort_unstable();
+            adj.dedup();
+        }
+        Indexed {
+            nodes,
+            out,
+            undirected,
+        }
+    }
+
+    fn find(&self, id: &str, is_tag: bool) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|n| n.id == id && n.is_tag == is_tag)
+    }
+
+    fn label(&self, i: usize) -> String {
+        let n = &self.nodes[i];
+        if n.is_tag {
+            format!("#{}", n.id)
+        } else {
+            n.id.clone()
+        }
+    }
+}
+
+/// Compute degree, PageRank, betweenness and communities for a graph.
+pub fn analyze(nodes: &[NoteNode], links: &[NoteLink]) -> GraphAnalytics {
+    let g = Indexed::new(nodes, links);
+    let n = nodes.len();
+
+    let mut in_degree = vec![0; n];
+    for adj in &g.out {
+        for &t in adj {
+            in_degree[t] += 1;
+        }
+    }
+    let pagerank = pagerank(&g.out);
+    let betweenness = betweenness(&g.undirected);
+    let (labels, communities) = communities(&g);
+
+    let mut out: Vec<NodeMetrics> = (0..n)
+        .map(|i| NodeMetrics {
+            id: nodes[i].id.clone(),
+            is_tag: nodes[i].is_tag,
+            degree: g.undirected[i].len(),
+            in_degree: in_degree[i],
+            out_degree: g.out[i].len(),
+            pagerank: pagerank[i],
+            betweenness: betweenness[i],
+            community: labels[i],
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.pagerank
+            .total_cmp(&a.pagerank)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    let index = out
+        .iter()
+        .enumerate()
+        .map(|(i, m)| ((m.id.clone(), m.is_tag), i))
+        .collect();
+    GraphAnalytics {
+        nodes: out,
+        communities,
+        index,
+    }
+}
+
+/// PageRank by power iteration. Rank from nodes without outgoing links is
+/// spread evenly over the graph.
+fn pagerank(out: &[Vec<usize>]) -> Vec<f64> {
+    let n = out.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..PAGERANK_MAX_ITER {
+        let dangling: f64 = (0..n).filter(|&i| out[i].is_empty()).map(|i| rank[i]).sum();
+        let base = (1.0 - DAMPING) / n as f64 + DAMPING * dangling / n as f64;
+        let mut next = vec![base; n];
+        for (i, adj) in out.iter().enumerate() {
+            if adj.is_empty() {
+                continue;
+            }
+            let share = DAMPING * rank[i] / adj.len() as f64;
+            for &t in adj {
+                next[t] += share;
+            }
+        }
+        let delta: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+    rank
+}
+
+/// Brandes' betweenness centrality on an undirected, unweighted graph,
+/// normalized to 0..1.
+fn betweenness(adj: &[Vec<usize>]) -> Vec<f64> {
+    let n = adj.len();
+    let mut bc = vec![0.0; n];
+    for s in 0..n {
+        let mut stack = Vec::new();
+        let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut sigma = vec![0.0; n];
+        let mut dist: Vec<Option<usize>> = vec![None; n];
+        sigma[s] = 1.0;
+        dist[s] = Some(0);
+        let mut queue = VecDeque::from([s]);
+        while let Some(v) = queue.pop_front() {
+            stack.push(v);
+            let dv = dist[v].unwrap_or(0);
+            for &w in &adj[v] {
+                if dist[w].is_none() {
+                    dist[w] = Some(dv + 1);
+                    queue.push_back(w);
+                }
+                if dist[w] == Some(dv + 1) {
+                    sigma[w] += sigma[v];
+                    preds[w].push(v);
+                }
+            }
+        }
+        let mut delta = vec![0.0; n];
+        while let Some(w) = stack.pop() {
+            for &v in &preds[w] {
+                delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+            }
+            if w != s {
+                bc[w] += delta[w];
+            }
+        }
+    }
+    // Each pair is counted from both ends; normalize by the number of pairs
+    // not including the node itself.
+    if n > 2 {
+        let scale = ((n - 1) * (n - 2)) as f64;
+        bc.iter_mut().for_each(|b| *b /= scale);
+    }
+    bc
+}
+
+/// Label propagation: each node repeatedly takes the most common label among
+/// its neighbors (lowest label on ties) until nothing changes. Communities
+/// are numbered by size, largest first.
+fn communities(g: &Indexed) -> (Vec<usize>, Vec<Community>) {
+    let n = g.nodes.len();
+    let mut labels: Vec<usize> = (0..n).collect();
+    for _ in 0..COMMUNITY_MAX_ITER {
+        let mut changed = false;
+        for v in 0..n {
+            if g.undirected[v].is_empty() {
+                continue;
+            }
+            let mut counts: HashMap<usize, usize> = HashMap::new();
+            for &w in &g.undirected[v] {
+                *counts.entry(labels[w]).or_default() += 1;
+            }
+            let best = counts
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .map(|(label, _)| label)
+                .unwrap_or(labels[v]);
+            if best != labels[v] {
+                labels[v] = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (v, &label) in labels.iter().enumerate() {
+        groups.entry(label).or_default().push(v);
+    }
+    let mut groups: Vec<Vec<usize>> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+
+    let mut out_labels = vec![0; n];
+    let communities = groups
+        .iter()
+        .enumerate()
+        .map(|(id, members)| {
+            for &v in members {
+                out_labels[v] = id;
+            }
+            let mut names: Vec<String> = members.iter().map(|&v| g.label(v)).collect();
+            names.sort();
+            Community {
+                id,
+                size: members.len(),
+                members: names,
+            }
+        })
+        .collect();
+    (out_labels, communities)
+}
+
+/// Shortest path between two notes, following links in either direction.
+/// Tag nodes are only used as stepping stones when `via_tags` is set.
+/// Returns node labels from `from` to `to`, tags prefixed with `#`.
+pub fn shortest_path(
+    nodes: &[NoteNode],
+    links: &[NoteLink],
+    from: &str,
+    to: &str,
+    via_tags: bool,
+) -> Option<Vec<String>> {
+    let g = Indexed::new(
+        nodes,
+        links.iter().filter(|l| via_tags || !l.kind.target_is_tag()),
+    );
+    let start = g.find(from, false)?;
+    let goal = g.find(to, false)?;
+
+    let mut prev: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut seen = vec![false; nodes.len()];
+    seen[start] = true;
+    let mut queue = VecDeque::from([start]);
+    while let Some(v) = queue.pop_front() {
+        if v == goal {
+            let mut path = vec![g.label(v)];
+            let mut cur = v;
+            while let Some(p) = prev[cur] {
+                path.push(g.label(p));
+                cur = p;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &w in &g.undirected[v] {
+            if !seen[w] {
+                seen[w] = true;
+                prev[w] = Some(v);
+                queue.push_back(w);
+            }
+        }
+    }
+    None
+}
@@ -1,39 +1,109 @@
-use crate::ai::{openai_keywords_blocking, openai_summarize_blocking};
-use crate::notes::note_path;
+use crate::ai::{AiJob, AiTask};
+use crate::tags::{self, TagOp};
 use crate::tui::AppState;
 use crate::MyError;
-use futures::executor::block_on;
 use std::fs;
-use tokio::task;
 
 /// Handle a command entered in the TUI.
 pub fn handle_cmd(cmd: String, st: &mut AppState) -> Result<(), MyError> {
     let trimmed = cmd.trim_start_matches(':').trim();
-    match trimmed {
-        "summarize" => {
-            if let Some(sn_ref) = st.selected_note() {
-                let sn = sn_ref.clone();
-                let content = fs::read_to_string(note_path(&sn))?;
-                let handle = task::spawn_blocking(move || openai_summarize_blocking(content));
-                let text = block_on(handle)??;
-                st.last_ai_output = Some(text);
-                st.status_message = Some(format!("AI Summarize done for '{}'", sn));
+    let (name, arg) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+    let arg = arg.trim();
+    if let Some(task) = AiTask::from_name(name) {
+        if let Some(sn_ref) = st.selected_note() {
+            let stem = sn_ref.clone();
+            let content = fs::read_to_string(st.note_file(&stem))?;
+            let busy = st.ai_jobs.running().is_some();
+            st.ai_jobs.push(AiJob {
+                task,
+                stem: stem.clone(),
+                content,
+            });
+            if busy {
+                st.status_message = Some(format!(
+                    "Queued AI {} for '{}' ({} waiting)",
+                    task.name(),
+                    stem,
+                    st.ai_jobs.queued()
+                ));
             }
         }
-        "keywords" => {
-            if let Some(sn_ref) = st.selected_note() {
-                let sn = sn_ref.clone();
-                let content = fs::read_to_string(note_path(&sn))?;
-                let handle = task::spawn_blocking(move || openai_keywords_blocking(content));
-                let text = block_on(handle)??;
-                st.last_ai_output = Some(text);
-                st.status_message = Some(format!("AI Keywords done for '{}'", sn));
+        return Ok(());
+    }
+    match name {
+        "saved" => {
+            if arg.is_empty() {
+                st.activate_saved_search(None);
+                st.status_message = Some("Showing all notes".to_string());
+            } else if st.saved_searches.iter().any(|s| s.name == arg) {
+                st.activate_saved_search(Some(arg.to_string()));
+                st.status_message = Some(format!("Saved search: {}", arg));
+            } else {
+                st.status_message = Some(format!("No saved search named '{}'", arg));
             }
         }
-        other => {
+        "tag" => {
+            let op = match parse_tag_cmd(arg, st.selected_note().cloned()) {
+                Ok(op) => op,
+                Err(usage) => {
+                    st.status_message = Some(usage);
+                    return Ok(());
+                }
+            };
+            let changed = tags::apply_tag_op(&op)?;
+            st.reload_notes();
+            st.update_preview();
+            st.status_message = Some(if changed.is_empty() {
+                "No notes changed".to_string()
+            } else {
+                format!("Updated {} note(s): {}", changed.len(), changed.join(", "))
+            });
+        }
+        _ => {
+            let other = trimmed;
             st.last_ai_output = Some(format!("Unknown command: '{}'", other));
             st.status_message = Some(format!("Unknown command: '{}'", other));
         }
     }
     Ok(())
 }
+
+/// Parse `:tag rename <old> <new>`, `:tag merge <a> <b>... --into <c>` and
+/// `:tag add|remove <tag> [notes...]`. Add and remove default to the
+/// selected note.
+fn parse_tag_cmd(arg: &str, selected: Option<String>) -> Result<TagOp, String> {
+    const USAGE: &str =
+        "Usage: tag rename <old> <new> | merge <tags...> --into <tag> | add|remove <tag> [notes...]";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let owned = |ws: &[&str]| ws.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+    match words.as_slice() {
+        ["rename", old, new] => Ok(TagOp::Rename {
+            from: old.to_string(),
+            to: new.to_string(),
+        }),
+        ["merge", rest @ ..] => match rest.iter().position(|w| *w == "--into") {
+            Some(i) if i > 0 && i + 2 == rest.len() => Ok(TagOp::Merge {
+                sources: owned(&rest[..i]),
+                into: rest[i + 1].to_string(),
+            }),
+            _ => Err(USAGE.to_string()),
+        },
+        [action @ ("add" | "remove"), tag, rest @ ..] => {
+            let notes = if rest.is_empty() {
+                selected.into_iter().collect()
+            } else {
+                owned(rest)
+            };
+            if notes.is_empty() {
+                return Err("No note selected".to_string());
+            }
+            let tag = tag.to_string();
+            Ok(if *action == "add" {
+                TagOp::Add { tag, notes }
+            } else {
+                TagOp::Remove { tag, notes }
+            })
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
@@ -0,0 +1,122 @@
+use clap::{Command, ValueEnum};
+use clap_complete::{generate, shells};
+use std::io::Write;
+
+/// Shells `nrs completions` can write a script for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// What `nrs complete` lists for the shell scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Candidates {
+    /// Note slugs
+    Notes,
+    /// Tag names, including parent tags
+    Tags,
+}
+
+/// Wraps the generated `_nrs` so note and tag arguments are completed from
+/// the vault.
+const BASH_DYNAMIC: &str = r#"
+_nrs_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local sub="${COMP_WORDS[1]}"
+    local kind=""
+    if [[ "$cur" != -* ]]; then
+        case "$sub" in
+            show|edit|path|append|backlinks|mv|render)
+                [[ $COMP_CWORD -eq 2 ]] && kind=notes ;;
+            link)
+                [[ $COMP_CWORD -eq 2 || $COMP_CWORD -eq 3 ]] && kind=notes ;;
+            list)
+                [[ "$prev" == -t || "$prev" == --tag ]] && kind=tags ;;
+            tag)
+                case "${COMP_WORDS[2]}" in
+                    rename|merge) [[ $COMP_CWORD -ge 3 ]] && kind=tags ;;
+                    add|remove)
+                        if [[ $COMP_CWORD -eq 3 ]]; then kind=tags
+                        elif [[ $COMP_CWORD -gt 3 ]]; then kind=notes
+                        fi ;;
+                esac ;;
+        esac
+    fi
+    if [[ -n "$kind" ]]; then
+        COMPREPLY=($(compgen -W "$(nrs complete "$kind" 2>/dev/null)" -- "$cur"))
+        return 0
+    fi
+    _nrs "$@"
+}
+
+if [[ "${BASH_VERSINFO[0]}" -eq 4 && "${BASH_VERSINFO[1]}" -ge 4 || "${BASH_VERSINFO[0]}" -gt 4 ]]; then
+    complete -F _nrs_dynamic -o nosort -o bashdefault -o default nrs
+else
+    complete -F _nrs_dynamic -o bashdefault -o default nrs
+fi
+"#;
+
+const ZSH_DYNAMIC: &str = r#"
+_nrs_dynamic() {
+    local kind=""
+    if [[ ${words[CURRENT]} != -* ]]; then
+        case ${words[2]} in
+            show|edit|path|append|backlinks|mv|render)
+                (( CURRENT == 3 )) && kind=notes ;;
+            link)
+                (( CURRENT == 3 || CURRENT == 4 )) && kind=notes ;;
+            list)
+                [[ ${words[CURRENT-1]} == (-t|--tag) ]] && kind=tags ;;
+            tag)
+                case ${words[3]} in
+                    rename|merge) (( CURRENT >= 4 )) && kind=tags ;;
+                    add|remove)
+                        if (( CURRENT == 4 )); then kind=tags
+                        elif (( CURRENT > 4 )); then kind=notes
+                        fi ;;
+                esac ;;
+        esac
+    fi
+    if [[ -n $kind ]]; then
+        local -a candidates
+        candidates=(${(f)"$(nrs complete $kind 2>/dev/null)"})
+        compadd -a candidates
+        return
+    fi
+    _nrs "$@"
+}
+
+compdef _nrs_dynamic nrs
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c nrs -n "__fish_seen_subcommand_from show edit path append backlinks link mv render" -f -a "(nrs complete notes 2>/dev/null)"
+complete -c nrs -n "__fish_seen_subcommand_from rename merge add remove" -f -a "(nrs complete tags 2>/dev/null)"
+complete -c nrs -n "__fish_seen_subcommand_from add remove" -f -a "(nrs complete notes 2>/dev/null)"
+complete -c nrs -n "__fish_seen_subcommand_from list" -s t -l tag -x -a "(nrs complete tags 2>/dev/null)"
+"#;
+
+/// Write the completion script for `shell`: clap's script for every
+/// subcommand and flag, followed by hooks that complete note and tag
+/// arguments by running `nrs complete`.
+pub fn write_completions(shell: CompletionShell, cmd: &mut Command, out: &mut impl Write) {
+    let name = cmd.get_name().to_string();
+    let dynamic = match shell {
+        CompletionShell::Bash => {
+            generate(shells::Bash, cmd, &name, out);
+            BASH_DYNAMIC
+        }
+        CompletionShell::Zsh => {
+            generate(shells::Zsh, cmd, &name, out);
+            ZSH_DYNAMIC
+        }
+        CompletionShell::Fish => {
+            generate(shells::Fish, cmd, &name, out);
+            FISH_DYNAMIC
+        }
+    };
+    let _ = out.write_all(dynamic.as_bytes());
+}
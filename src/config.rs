@@ -0,0 +1,151 @@
+use crate::notes::notes_dir;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Directory holding vault configuration and caches, `~/notes/.nrs`.
+pub fn vault_meta_dir() -> PathBuf {
+    notes_dir().join(".nrs")
+}
+
+/// Path to the vault configuration file.
+pub fn config_path() -> PathBuf {
+    vault_meta_dir().join("config.json")
+}
+
+/// A named query shown as a virtual folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+/// Note that `nrs capture` appends to when `inbox` is not set.
+pub const DEFAULT_INBOX: &str = "inbox";
+
+/// How new links between notes are written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStyle {
+    /// `[[note]]`
+    #[default]
+    Wikilink,
+    /// `[Title](relative/path/note.md)`
+    Markdown,
+}
+
+/// Where daily, weekly or monthly notes live and how they are named. Unset
+/// fields use the defaults of the period.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PeriodicSettings {
+    /// Vault folder, e.g. `journal/daily`.
+    pub folder: Option<String>,
+    /// chrono format for the file name, e.g. `%Y-%m-%d`. May contain `/`
+    /// to nest notes in subfolders.
+    pub format: Option<String>,
+    /// Template from the templates folder for new notes.
+    pub template: Option<String>,
+}
+
+/// What to do when a new note's file name is already taken.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Fail with a conflict error.
+    #[default]
+    Error,
+    /// Append `-2`, `-3`, ... to the file name.
+    Suffix,
+    /// Return the existing note instead.
+    Open,
+}
+
+/// How note titles are turned into file names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlugSettings {
+    /// Longest file name, in characters, before any collision suffix.
+    pub max_length: usize,
+    /// Replaces spaces and punctuation.
+    pub separator: String,
+    pub on_conflict: ConflictPolicy,
+}
+
+impl Default for SlugSettings {
+    fn default() -> Self {
+        SlugSettings {
+            max_length: 80,
+            separator: "_".to_string(),
+            on_conflict: ConflictPolicy::Error,
+        }
+    }
+}
+
+/// Which backend runs AI commands.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AiProviderKind {
+    /// An OpenAI-compatible chat completions API.
+    #[default]
+    Openai,
+    /// Offline answers derived from the note text, for testing.
+    Mock,
+}
+
+/// AI provider settings. Unset fields use OpenAI's endpoint, the
+/// `OPENAI_API_KEY` variable and the default model.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiSettings {
+    pub provider: AiProviderKind,
+    /// API root, e.g. `http://localhost:11434/v1` for Ollama.
+    pub base_url: Option<String>,
+    /// Environment variable holding the API key.
+    pub api_key_env: Option<String>,
+    /// Model for every task without an entry in `models`.
+    pub model: Option<String>,
+    /// Model per task name, e.g. `{"keywords": "llama3.2"}`.
+    pub models: HashMap<String, String>,
+}
+
+/// Per-vault settings stored in `~/notes/.nrs/config.json`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultConfig {
+    pub saved_searches: Vec<SavedSearch>,
+    pub link_style: LinkStyle,
+    pub daily: PeriodicSettings,
+    pub weekly: PeriodicSettings,
+    pub monthly: PeriodicSettings,
+    pub slug: SlugSettings,
+    /// Note that `nrs capture` appends to.
+    pub inbox: Option<String>,
+    pub ai: AiSettings,
+}
+
+impl VaultConfig {
+    /// Look up a saved search by name.
+    pub fn saved_search(&self, name: &str) -> Option<&SavedSearch> {
+        self.saved_searches.iter().find(|s| s.name == name)
+    }
+}
+
+/// Load the vault configuration, with defaults if it is missing. An invalid
+/// file is an error describing the problem.
+pub fn try_load_config() -> Result<VaultConfig, String> {
+    let path = config_path();
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e)),
+        Err(_) => Ok(VaultConfig::default()),
+    }
+}
+
+/// Load the vault configuration, falling back to defaults if it is missing
+/// or invalid.
+pub fn load_config() -> VaultConfig {
+    try_load_config().unwrap_or_default()
+}
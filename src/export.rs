@@ -0,0 +1,380 @@
+use crate::analytics;
+use crate::graph::{self, LinkKind, NoteInfo, NoteLink, NoteNode};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Output formats for `nrs graph export` and `GET /api/graph-data`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// The d3 `nodes`/`links` JSON used by the web UI.
+    #[default]
+    Json,
+    /// Graphviz DOT.
+    Dot,
+    /// GraphML, for Gephi, yEd and networkx.
+    Graphml,
+    /// GEXF 1.3, Gephi's native format.
+    Gexf,
+}
+
+impl GraphFormat {
+    /// MIME type of the rendered graph.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            GraphFormat::Json => "application/json",
+            GraphFormat::Dot => "text/vnd.graphviz; charset=utf-8",
+            GraphFormat::Graphml | GraphFormat::Gexf => "application/xml; charset=utf-8",
+        }
+    }
+}
+
+/// A graph node with the attributes written by every export format.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportNode {
+    pub id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub is_tag: bool,
+    /// False for link targets that have no note yet.
+    pub exists: bool,
+    /// Number of links touching the node, in either direction.
+    pub degree: usize,
+    pub mtime: Option<u64>,
+    /// Only computed when asked for, since betweenness is costly.
+    #[serde(flatten)]
+    pub centrality: Option<NodeCentrality>,
+}
+
+/// Centrality and community of a node, from [`analytics::analyze`].
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeCentrality {
+    pub pagerank: f64,
+    pub betweenness: f64,
+    pub community: usize,
+}
+
+/// A graph edge with its kind, weight and context.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportLink {
+    pub source: String,
+    pub target: String,
+    #[serde(rename = "type")]
+    pub kind: LinkKind,
+    pub weight: usize,
+    pub context: Option<String>,
+}
+
+/// The note graph with node attributes, ready to be written out.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportGraph {
+    pub nodes: Vec<ExportNode>,
+    pub links: Vec<ExportLink>,
+}
+
+impl ExportGraph {
+    /// Build the attributed graph from scanned notes, with centrality if
+    /// `centrality` is set.
+    pub fn from_notes(notes: &[NoteInfo], centrality: bool) -> Self {
+        let (nodes, links) = graph::graph_from_notes(notes);
+        Self::from_graph(nodes, links, notes, centrality)
+    }
+
+    /// Attach note attributes to already built (and possibly filtered) graph
+    /// nodes and links. Centrality is computed on that graph if `centrality`
+    /// is set.
+    pub fn from_graph(
+        nodes: Vec<NoteNode>,
+        links: Vec<NoteLink>,
+        notes: &[NoteInfo],
+        centrality: bool,
+    ) -> Self {
+        let metrics = centrality.then(|| analytics::analyze(&nodes, &links));
+        let by_stem: HashMap<&str, &NoteInfo> =
+            notes.iter().map(|n| (n.stem.as_str(), n)).collect();
+
+        // Degree per (id, is_tag), since a tag and a note may share a name.
+        let mut degree: HashMap<(&str, bool), usize> = HashMap::new();
+        for l in &links {
+            *degree
+                .entry((&l.source, l.kind.source_is_tag()))
+                .or_default() += 1;
+            *degree
+                .entry((&l.target, l.kind.target_is_tag()))
+                .or_default() += 1;
+        }
+
+        let mut out_nodes: Vec<ExportNode> = nodes
+            .iter()
+            .map(|n| {
+                let info = (!n.is_tag).then(|| by_stem.get(n.id.as_str())).flatten();
+                ExportNode {
+                    id: n.id.clone(),
+                    title: info
+                        .map(|i| i.title.clone())
+                        .unwrap_or_else(|| n.id.clone()),
+                    tags: info.map(|i| i.tags.clone()).unwrap_or_default(),
+                    is_tag: n.is_tag,
+                    exists: n.is_tag || info.is_some(),
+                    degree: degree.get(&(n.id.as_str(), n.is_tag)).copied().unwrap_or(0),
+                    mtime: info.and_then(|i| i.mtime),
+                    centrality: metrics.as_ref().map(|metrics| {
+                        let m = metrics.get(&n.id, n.is_tag);
+                        NodeCentrality {
+                            pagerank: m.map(|m| m.pagerank).unwrap_or(0.0),
+                            betweenness: m.map(|m| m.betweenness).unwrap_or(0.0),
+                            community: m.map(|m| m.community).unwrap_or(0),
+                        }
+                    }),
+                }
+            })
+            .collect();
+        out_nodes.sort_by(|a, b| (a.is_tag, &a.id).cmp(&(b.is_tag, &b.id)));
+
+        let out_links = links
+            .into_iter()
+            .map(|l| ExportLink {
+                source: l.source,
+                target: l.target,
+                kind: l.kind,
+                weight: l.weight,
+                context: l.context,
+            })
+            .collect();
+
+        ExportGraph {
+            nodes: out_nodes,
+            links: out_links,
+        }
+    }
+
+    /// Render the graph in `format`.
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Graphml => self.to_graphml(),
+            GraphFormat::Gexf => self.to_gexf(),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph notes {\n");
+        for n in &self.nodes {
+            let _ = writeln!(
+                out,
+                "  {} [label={}, shape={}, is_tag={}, exists={}, degree={}{}, tags={}{}];",
+                dot_quote(&file_node_id(&n.id, n.is_tag)),
+                dot_quote(&n.title),
+                if n.is_tag { "box" } else { "ellipse" },
+                n.is_tag,
+                n.exists,
+                n.degree,
+                n.centrality
+                    .as_ref()
+                    .map(|c| format!(
+                        ", pagerank={:.6}, betweenness={:.6}, community={}",
+                        c.pagerank, c.betweenness, c.community
+                    ))
+                    .unwrap_or_default(),
+                dot_quote(&n.tags.join(",")),
+                n.mtime
+                    .map(|m| format!(", mtime={}", m))
+                    .unwrap_or_default(),
+            );
+        }
+        for l in &self.links {
+            let _ = writeln!(
+                out,
+                "  {} -> {} [type={}, weight={}{}];",
+                dot_quote(&file_node_id(&l.source, l.kind.source_is_tag())),
+                dot_quote(&file_node_id(&l.target, l.kind.target_is_tag())),
+                l.kind.label(),
+                l.weight,
+                l.context
+                    .as_deref()
+                    .map(|c| format!(", context={}", dot_quote(c)))
+                    .unwrap_or_default(),
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn to_graphml(&self) -> String {
+        let mut out = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="title" for="node" attr.name="title" attr.type="string"/>
+  <key id="tags" for="node" attr.name="tags" attr.type="string"/>
+  <key id="is_tag" for="node" attr.name="is_tag" attr.type="boolean"/>
+  <key id="exists" for="node" attr.name="exists" attr.type="boolean"/>
+  <key id="degree" for="node" attr.name="degree" attr.type="int"/>
+  <key id="mtime" for="node" attr.name="mtime" attr.type="long"/>
+  <key id="pagerank" for="node" attr.name="pagerank" attr.type="double"/>
+  <key id="betweenness" for="node" attr.name="betweenness" attr.type="double"/>
+  <key id="community" for="node" attr.name="community" attr.type="int"/>
+  <key id="type" for="edge" attr.name="type" attr.type="string"/>
+  <key id="weight" for="edge" attr.name="weight" attr.type="int"/>
+  <key id="context" for="edge" attr.name="context" attr.type="string"/>
+  <graph id="notes" edgedefault="directed">
+"#,
+        );
+        for n in &self.nodes {
+            let _ = writeln!(
+                out,
+                "    <node id=\"{}\">",
+                xml_escape(&file_node_id(&n.id, n.is_tag))
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"title\">{}</data>",
+                xml_escape(&n.title)
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"tags\">{}</data>",
+                xml_escape(&n.tags.join(","))
+            );
+            let _ = writeln!(out, "      <data key=\"is_tag\">{}</data>", n.is_tag);
+            let _ = writeln!(out, "      <data key=\"exists\">{}</data>", n.exists);
+            let _ = writeln!(out, "      <data key=\"degree\">{}</data>", n.degree);
+            if let Some(c) = &n.centrality {
+                let _ = writeln!(out, "      <data key=\"pagerank\">{}</data>", c.pagerank);
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"betweenness\">{}</data>",
+                    c.betweenness
+                );
+                let _ = writeln!(out, "      <data key=\"community\">{}</data>", c.community);
+            }
+            if let Some(m) = n.mtime {
+                let _ = writeln!(out, "      <data key=\"mtime\">{}</data>", m);
+            }
+            out.push_str("    </node>\n");
+        }
+        for (i, l) in self.links.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"><data key=\"type\">{}</data><data key=\"weight\">{}</data><data key=\"context\">{}</data></edge>",
+                i,
+                xml_escape(&file_node_id(&l.source, l.kind.source_is_tag())),
+                xml_escape(&file_node_id(&l.target, l.kind.target_is_tag())),
+                l.kind.label(),
+                l.weight,
+                xml_escape(l.context.as_deref().unwrap_or_default()),
+            );
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    fn to_gexf(&self) -> String {
+        let mut out = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<gexf xmlns="http://gexf.net/1.3" version="1.3">
+  <graph defaultedgetype="directed">
+    <attributes class="node">
+      <attribute id="tags" title="tags" type="string"/>
+      <attribute id="is_tag" title="is_tag" type="boolean"/>
+      <attribute id="exists" title="exists" type="boolean"/>
+      <attribute id="degree" title="degree" type="integer"/>
+      <attribute id="mtime" title="mtime" type="long"/>
+      <attribute id="pagerank" title="pagerank" type="double"/>
+      <attribute id="betweenness" title="betweenness" type="double"/>
+      <attribute id="community" title="community" type="integer"/>
+    </attributes>
+    <attributes class="edge">
+      <attribute id="type" title="type" type="string"/>
+      <attribute id="context" title="context" type="string"/>
+    </attributes>
+    <nodes>
+"#,
+        );
+        for n in &self.nodes {
+            let _ = writeln!(
+                out,
+                "      <node id=\"{}\" label=\"{}\">",
+                xml_escape(&file_node_id(&n.id, n.is_tag)),
+                xml_escape(&n.title)
+            );
+            out.push_str("        <attvalues>\n");
+            let _ = writeln!(
+                out,
+                "          <attvalue for=\"tags\" value=\"{}\"/>",
+                xml_escape(&n.tags.join(","))
+            );
+            let _ = writeln!(
+                out,
+                "          <attvalue for=\"is_tag\" value=\"{}\"/>",
+                n.is_tag
+            );
+            let _ = writeln!(
+                out,
+                "          <attvalue for=\"exists\" value=\"{}\"/>",
+                n.exists
+            );
+            let _ = writeln!(
+                out,
+                "          <attvalue for=\"degree\" value=\"{}\"/>",
+                n.degree
+            );
+            if let Some(c) = &n.centrality {
+                for (key, value) in [
+                    ("pagerank", c.pagerank.to_string()),
+                    ("betweenness", c.betweenness.to_string()),
+                    ("community", c.community.to_string()),
+                ] {
+                    let _ = writeln!(
+                        out,
+                        "          <attvalue for=\"{}\" value=\"{}\"/>",
+                        key, value
+                    );
+                }
+            }
+            if let Some(m) = n.mtime {
+                let _ = writeln!(out, "          <attvalue for=\"mtime\" value=\"{}\"/>", m);
+            }
+            out.push_str("        </attvalues>\n      </node>\n");
+        }
+        out.push_str("    </nodes>\n    <edges>\n");
+        for (i, l) in self.links.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\" weight=\"{}\"><attvalues><attvalue for=\"type\" value=\"{}\"/><attvalue for=\"context\" value=\"{}\"/></attvalues></edge>",
+                i,
+                xml_escape(&file_node_id(&l.source, l.kind.source_is_tag())),
+                xml_escape(&file_node_id(&l.target, l.kind.target_is_tag())),
+                l.weight,
+                l.kind.label(),
+                xml_escape(l.context.as_deref().unwrap_or_default()),
+            );
+        }
+        out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+        out
+    }
+}
+
+/// Node id in file formats. Tags get a `#` prefix so a tag and a note with
+/// the same name stay separate nodes.
+fn file_node_id(id: &str, is_tag: bool) -> String {
+    if is_tag {
+        format!("#{}", id)
+    } else {
+        id.to_string()
+    }
+}
+
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,492 @@
+use crate::links::{self, LinkRef};
+use crate::notes::{self, parse_front_matter, walk_notes};
+use crate::semantic::{self, VectorIndex};
+use crate::tags;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+/// A note node in the graph.
+#[derive(Debug, Serialize)]
+pub struct NoteNode {
+    pub id: String,
+    pub is_tag: bool,
+}
+
+/// What a graph link represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// A `[[wikilink]]` from one note to another.
+    Wikilink,
+    /// A Markdown `[text](note.md)` link.
+    Markdown,
+    /// A `![[note]]` embed.
+    Embed,
+    /// A wikilink in a front matter field, e.g. `parent: "[[note]]"`.
+    Relation,
+    /// A note carrying a tag.
+    Tag,
+    /// A parent tag containing a nested tag.
+    NestedTag,
+}
+
+impl LinkKind {
+    pub const ALL: [LinkKind; 6] = [
+        LinkKind::Wikilink,
+        LinkKind::Markdown,
+        LinkKind::Embed,
+        LinkKind::Relation,
+        LinkKind::Tag,
+        LinkKind::NestedTag,
+    ];
+
+    /// Parse a kind from its label, e.g. `wikilink`.
+    pub fn from_label(s: &str) -> Option<LinkKind> {
+        LinkKind::ALL.into_iter().find(|k| k.label() == s)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkKind::Wikilink => "wikilink",
+            LinkKind::Markdown => "markdown",
+            LinkKind::Embed => "embed",
+            LinkKind::Relation => "relation",
+            LinkKind::Tag => "tag",
+            LinkKind::NestedTag => "nested_tag",
+        }
+    }
+
+    /// Whether links of this kind start at a tag node.
+    pub fn source_is_tag(&self) -> bool {
+        *self == LinkKind::NestedTag
+    }
+
+    /// Whether links of this kind end at a tag node.
+    pub fn target_is_tag(&self) -> bool {
+        matches!(self, LinkKind::Tag | LinkKind::NestedTag)
+    }
+}
+
+/// A note link in the graph.
+#[derive(Debug, Serialize)]
+pub struct NoteLink {
+    pub source: String,
+    pub target: String,
+    pub kind: LinkKind,
+    /// How many times the source links to the target this way.
+    pub weight: usize,
+    /// The sentence containing the first occurrence of the link.
+    pub context: Option<String>,
+}
+
+/// Links and tags extracted from a single note.
+#[derive(Debug, Clone)]
+pub struct NoteInfo {
+    pub stem: String,
+    /// Path from the vault root.
+    pub path: String,
+    /// Front matter title, or the stem with underscores as spaces.
+    pub title: String,
+    /// Modification time in Unix seconds.
+    pub mtime: Option<u64>,
+    /// Targets of every reference to another note, in order of appearance.
+    pub links: Vec<String>,
+    /// Every note reference with its kind and context.
+    pub refs: Vec<LinkRef>,
+    pub tags: Vec<String>,
+}
+
+/// Normalize a link target the way graph node ids are stored.
+pub fn normalize_id(s: &str) -> String {
+    s.trim().to_lowercase().replace(' ', "_")
+}
+
+/// Id of the note node `name` refers to: the node with that exact id, or
+/// else the node of the note the name resolves to, as a link would.
+pub fn note_node_id(nodes: &[NoteNode], name: &str) -> Option<String> {
+    let is_note = |id: &str| nodes.iter().any(|n| !n.is_tag && n.id == id);
+    if is_note(name) {
+        return Some(name.to_string());
+    }
+    let stem = notes::resolve_note(name)?
+        .file_stem()?
+        .to_string_lossy()
+        .to_string();
+    is_note(&stem).then_some(stem)
+}
+
+/// Extract links and tags from every note in the vault.
+pub fn scan_vault() -> Vec<NoteInfo> {
+    let mut out = Vec::new();
+    for p in walk_notes() {
+        let stem = p.file_stem().unwrap().to_string_lossy().to_string();
+        let content = fs::read_to_string(&p).unwrap_or_default();
+
+        // Find [[links]], embeds, Markdown links and relations in content.
+        let refs = links::extract_links(&content);
+        let links = refs
+            .iter()
+            .filter(|r| !r.target.is_empty())
+            .map(|r| r.target.clone())
+            .collect();
+
+        // Tags from the front matter and inline #tags.
+        let tags = tags::extract_tags(&content);
+
+        let title = parse_front_matter(&content)
+            .title
+            .unwrap_or_else(|| stem.replace('_', " "));
+        let mtime = fs::metadata(&p)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+
+        out.push(NoteInfo {
+            stem,
+            path: notes::relative_path(&p),
+            title,
+            mtime,
+            links,
+            refs,
+            tags,
+        });
+    }
+    out
+}
+
+/// Build the graph from note content.
+pub fn build_graph() -> (Vec<NoteNode>, Vec<NoteLink>) {
+    graph_from_notes(&scan_vault())
+}
+
+/// Build graph nodes and links from scanned notes.
+pub fn graph_from_notes(notes: &[NoteInfo]) -> (Vec<NoteNode>, Vec<NoteLink>) {
+    // Build sets of nodes and links.
+    let mut note_set = HashSet::new();
+    let mut tag_set = HashSet::new();
+    let mut links = Vec::new();
+
+    for info in notes {
+        note_set.insert(info.stem.clone());
+        for l in &info.links {
+            note_set.insert(l.clone());
+        }
+        for t in &info.tags {
+            tag_set.extend(tags::tag_ancestry(t));
+        }
+    }
+
+    let mut note_set: Vec<String> = note_set.into_iter().collect();
+    note_set.sort();
+    let mut tag_set: Vec<String> = tag_set.into_iter().collect();
+    tag_set.sort();
+
+    let mut nodes = Vec::new();
+    for n in note_set {
+        nodes.push(NoteNode {
+            id: n,
+            is_tag: false,
+        });
+    }
+    for t in &tag_set {
+        nodes.push(NoteNode {
+            id: t.clone(),
+            is_tag: true,
+        });
+    }
+
+    // Link parent tags to their nested child tags.
+    for t in &tag_set {
+        if let Some(parent) = tags::parent_tag(t) {
+            links.push(NoteLink {
+                source: parent.to_string(),
+                target: t.clone(),
+                kind: LinkKind::NestedTag,
+                weight: 1,
+                context: None,
+            });
+        }
+    }
+
+    for info in notes {
+        // Repeated links of the same kind become one weighted link.
+        let mut seen: HashMap<(&str, LinkKind), usize> = HashMap::new();
+        for r in info.refs.iter().filter(|r| !r.target.is_empty()) {
+            match seen.get(&(r.target.as_str(), r.kind)) {
+                Some(&i) => links[i].weight += 1,
+                None => {
+                    seen.insert((&r.target, r.kind), links.len());
+                    links.push(NoteLink {
+                        source: info.stem.clone(),
+                        target: r.target.clone(),
+                        kind: r.kind,
+                        weight: 1,
+                        context: r.context.clone(),
+                    });
+                }
+            }
+        }
+        for t in &info.tags {
+            links.push(NoteLink {
+                source: info.stem.clone(),
+                target: t.clone(),
+                kind: LinkKind::Tag,
+                weight: 1,
+                context: None,
+            });
+        }
+    }
+
+    (nodes, links)
+}
+
+/// Restricts the graph to a sub-graph. The default keeps everything.
+#[derive(Debug, Clone, Default)]
+pub struct GraphFilter {
+    /// Keep only nodes within `depth` links of this note.
+    pub focus: Option<String>,
+    /// Neighborhood depth around `focus`, following links in either direction.
+    pub depth: usize,
+    /// Keep only notes carrying at least one of these tags (or a child tag).
+    pub include_tags: Vec<String>,
+    /// Drop notes carrying any of these tags (or a child tag).
+    pub exclude_tags: Vec<String>,
+    /// Drop tag nodes and their links.
+    pub hide_tags: bool,
+    /// Drop nodes left without any links.
+    pub hide_orphans: bool,
+    /// Keep only notes modified at or after this Unix time.
+    pub since: Option<u64>,
+    /// Keep only links of these kinds; empty keeps all.
+    pub kinds: Vec<LinkKind>,
+}
+
+/// Apply `filter` to graph nodes and links built from `notes`. Links whose
+/// endpoints were filtered out are dropped too.
+pub fn filter_graph(
+    nodes: Vec<NoteNode>,
+    links: Vec<NoteLink>,
+    notes: &[NoteInfo],
+    filter: &GraphFilter,
+) -> (Vec<NoteNode>, Vec<NoteLink>) {
+    let by_stem: HashMap<&str, &NoteInfo> = notes.iter().map(|n| (n.stem.as_str(), n)).collect();
+
+    let keep_note = |id: &str| {
+        let info = by_stem.get(id);
+        if !filter.include_tags.is_empty()
+            && !info.is_some_and(|i| {
+                filter
+                    .include_tags
+                    .iter()
+                    .any(|q| i.tags.iter().any(|t| tags::tag_matches(q, t)))
+            })
+        {
+            return false;
+        }
+        if info.is_some_and(|i| {
+            filter
+                .exclude_tags
+                .iter()
+                .any(|q| i.tags.iter().any(|t| tags::tag_matches(q, t)))
+        }) {
+            return false;
+        }
+        match filter.since {
+            Some(since) => info.and_then(|i| i.mtime).is_some_and(|m| m >= since),
+            None => true,
+        }
+    };
+
+    let mut kept: HashSet<(String, bool)> = nodes
+        .iter()
+        .filter(|n| !n.is_tag && keep_note(&n.id))
+        .map(|n| (n.id.clone(), false))
+        .collect();
+
+    // With a note filter active, only keep tags still used by a kept note.
+    let note_filter = !filter.include_tags.is_empty()
+        || !filter.exclude_tags.is_empty()
+        || filter.since.is_some();
+    let used_tags: HashSet<String> = notes
+        .iter()
+        .filter(|n| kept.contains(&(n.stem.clone(), false)))
+        .flat_map(|n| n.tags.iter().flat_map(|t| tags::tag_ancestry(t)))
+        .collect();
+    if !filter.hide_tags {
+        kept.extend(
+            nodes
+                .iter()
+                .filter(|n| n.is_tag && (!note_filter || used_tags.contains(&n.id)))
+                .map(|n| (n.id.clone(), true)),
+        );
+    }
+
+    let endpoints = |l: &NoteLink| {
+        (
+            (l.source.clone(), l.kind.source_is_tag()),
+            (l.target.clone(), l.kind.target_is_tag()),
+        )
+    };
+    let mut links: Vec<NoteLink> = links
+        .into_iter()
+        .filter(|l| filter.kinds.is_empty() || filter.kinds.contains(&l.kind))
+        .filter(|l| {
+            let (s, t) = endpoints(l);
+            kept.contains(&s) && kept.contains(&t)
+        })
+        .collect();
+
+    if let Some(focus) = &filter.focus {
+        let mut adjacent: HashMap<(String, bool), Vec<(String, bool)>> = HashMap::new();
+        for l in &links {
+            let (s, t) = endpoints(l);
+            adjacent.entry(s.clone()).or_default().push(t.clone());
+            adjacent.entry(t).or_default().push(s);
+        }
+        let start = (focus.clone(), false);
+        let mut reached = HashSet::new();
+        if kept.contains(&start) {
+            reached.insert(start.clone());
+        }
+        let mut frontier = vec![start];
+        for _ in 0..filter.depth {
+            let mut next = Vec::new();
+            for node in &frontier {
+                for n in adjacent.get(node).into_iter().flatten() {
+                    if reached.insert(n.clone()) {
+                        next.push(n.clone());
+                    }
+                }
+            }
+            frontier = next;
+        }
+        kept = reached;
+        links.retain(|l| {
+            let (s, t) = endpoints(l);
+            kept.contains(&s) && kept.contains(&t)
+        });
+    }
+
+    if filter.hide_orphans {
+        let linked: HashSet<(String, bool)> = links
+            .iter()
+            .flat_map(|l| {
+                let (s, t) = endpoints(l);
+                [s, t]
+            })
+            .collect();
+        kept.retain(|n| linked.contains(n));
+    }
+
+    let nodes = nodes
+        .into_iter()
+        .filter(|n| kept.contains(&(n.id.clone(), n.is_tag)))
+        .collect();
+    (nodes, links)
+}
+
+/// Per-reason scores for a related note, each between 0 and 1.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RelatedReasons {
+    /// Overlap of the two notes' tag sets.
+    pub tags: f64,
+    /// Direct links, shared link targets and co-citation by other notes.
+    pub links: f64,
+    /// Cosine similarity of the notes' text embeddings.
+    pub text: f64,
+}
+
+/// A note related to another, with the combined score and its reasons.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedNote {
+    pub slug: String,
+    pub score: f64,
+    pub reasons: RelatedReasons,
+    pub shared_tags: Vec<String>,
+}
+
+/// Minimum combined score for a note to be suggested as related.
+const RELATED_MIN_SCORE: f64 = 0.05;
+
+/// Suggest notes related to `stem` by shared tags, shared links and text
+/// similarity. `index` supplies text embeddings; without it only tags and
+/// links are used.
+pub fn related_notes(
+    notes: &[NoteInfo],
+    index: Option<&VectorIndex>,
+    stem: &str,
+    limit: usize,
+) -> Vec<RelatedNote> {
+    let Some(me) = notes.iter().find(|n| n.stem == stem) else {
+        return Vec::new();
+    };
+    let my_tags: HashSet<&String> = me.tags.iter().collect();
+    // Link targets and stems are compared by id, like links are resolved.
+    let link_ids =
+        |n: &NoteInfo| -> HashSet<String> { n.links.iter().map(|l| normalize_id(l)).collect() };
+    let my_id = normalize_id(stem);
+    let my_links = link_ids(me);
+
+    // Notes that link to `stem`, for co-citation.
+    let mut co_cited: HashMap<String, usize> = HashMap::new();
+    for citer in notes.iter().filter(|n| n.stem != stem) {
+        let targets = link_ids(citer);
+        if targets.contains(&my_id) {
+            for target in targets {
+                *co_cited.entry(target).or_default() += 1;
+            }
+        }
+    }
+
+    let my_vector = index.and_then(|i| i.vector(&me.path));
+
+    let mut out: Vec<RelatedNote> = notes
+        .iter()
+        .filter(|other| other.stem != stem)
+        .map(|other| {
+            let other_tags: HashSet<&String> = other.tags.iter().collect();
+            let mut shared_tags: Vec<String> = my_tags
+                .intersection(&other_tags)
+                .map(|t| t.to_string())
+                .collect();
+            shared_tags.sort();
+            let tags = if shared_tags.is_empty() {
+                0.0
+            } else {
+                shared_tags.len() as f64 / ((my_tags.len() * other_tags.len()) as f64).sqrt()
+            };
+
+            let other_id = normalize_id(&other.stem);
+            let other_links = link_ids(other);
+            let direct = my_links.contains(&other_id) || other_links.contains(&my_id);
+            let shared_targets = my_links.intersection(&other_links).count();
+            let raw_links =
+                direct as usize + shared_targets + co_cited.get(&other_id).copied().unwrap_or(0);
+            let links = raw_links as f64 / (raw_links as f64 + 1.0);
+
+            let text = match (my_vector, index.and_then(|i| i.vector(&other.path))) {
+                (Some(a), Some(b)) => semantic::cosine(a, b).max(0.0) as f64,
+                _ => 0.0,
+            };
+
+            RelatedNote {
+                slug: other.stem.clone(),
+                score: tags + links + text,
+                reasons: RelatedReasons { tags, links, text },
+                shared_tags,
+            }
+        })
+        .filter(|r| r.score >= RELATED_MIN_SCORE)
+        .collect();
+
+    out.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.slug.cmp(&b.slug))
+    });
+    out.truncate(limit);
+    out
+}
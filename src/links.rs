@@ -0,0 +1,718 @@
+use crate::config::{self, LinkStyle};
+use crate::graph::{normalize_id, LinkKind};
+use crate::notes;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Longest link context kept, in characters.
+const MAX_CONTEXT: usize = 240;
+
+/// The part of a note a link points to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum Anchor {
+    /// `[[note#Heading]]`
+    Heading(String),
+    /// `[[note#^block-id]]`
+    Block(String),
+}
+
+impl Anchor {
+    /// Parse the part after `#`: `^id` is a block, anything else a heading.
+    pub fn parse(fragment: &str) -> Option<Anchor> {
+        let fragment = fragment.trim();
+        match fragment.strip_prefix('^') {
+            _ if fragment.is_empty() => None,
+            Some(id) => Some(Anchor::Block(id.to_string())),
+            None => Some(Anchor::Heading(fragment.to_string())),
+        }
+    }
+
+    /// The HTML id the anchor maps to: the heading slug or `^block-id`.
+    pub fn fragment(&self) -> String {
+        match self {
+            Anchor::Heading(h) => heading_slug(h),
+            Anchor::Block(id) => format!("^{}", id),
+        }
+    }
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Anchor::Heading(h) => write!(f, "#{}", h),
+            Anchor::Block(id) => write!(f, "#^{}", id),
+        }
+    }
+}
+
+/// The parts of `[[target#anchor|label]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    /// The target as written; empty for links within the same note.
+    pub target: String,
+    pub anchor: Option<Anchor>,
+    pub label: Option<String>,
+}
+
+/// Parse the inside of a wikilink: `note`, `note#Heading`, `note#^block`,
+/// `note|label` or `#Heading` for the same note.
+pub fn parse_wikilink(inner: &str) -> WikiLink {
+    let (reference, label) = match inner.split_once('|') {
+        Some((r, l)) => (r, Some(l.trim().to_string()).filter(|l| !l.is_empty())),
+        None => (inner, None),
+    };
+    let (target, anchor) = match reference.split_once('#') {
+        Some((t, a)) => (t, Anchor::parse(a)),
+        None => (reference, None),
+    };
+    WikiLink {
+        target: target.trim().to_string(),
+        anchor,
+        label,
+    }
+}
+
+/// A reference from a note to another note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRef {
+    /// Normalized stem of the linked note; empty for links within the note.
+    pub target: String,
+    pub kind: LinkKind,
+    /// The sentence containing the link, or the front matter line for
+    /// relations.
+    pub context: Option<String>,
+    pub anchor: Option<Anchor>,
+    /// Display text, from `[[note|label]]` or `[label](note.md)`.
+    pub label: Option<String>,
+    /// File path of a Markdown link, relative to the note's folder.
+    pub path: Option<String>,
+    /// The link as written.
+    pub raw: String,
+    /// 1-based line number in the note.
+    pub line: usize,
+}
+
+/// A Markdown heading in a note.
+#[derive(Debug, Clone, Serialize)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    /// HTML id, unique within the note (`intro`, `intro-1`, ...).
+    pub slug: String,
+    /// 1-based line number in the note.
+    pub line: usize,
+}
+
+/// GitHub-style heading id: lowercase, spaces to `-`, punctuation dropped.
+pub fn heading_slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Body lines outside code fences, with their 1-based line numbers in the
+/// full note.
+fn body_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let (_, body) = notes::split_front_matter(content);
+    let offset = content[..content.len() - body.len()].matches('\n').count();
+    let mut in_fence = false;
+    body.lines().enumerate().filter_map(move |(i, line)| {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            return None;
+        }
+        (!in_fence).then_some((offset + i + 1, line))
+    })
+}
+
+/// The ATX headings of a note, outside code blocks.
+pub fn headings(content: &str) -> Vec<Heading> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| Regex::new(r"^(#{1,6})\s+(.+?)\s*#*\s*$").unwrap());
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    body_lines(content)
+        .filter_map(|(line, text)| {
+            let caps = re.captures(text)?;
+            let text = caps[2].to_string();
+            let base = heading_slug(&text);
+            let n = seen.entry(base.clone()).or_default();
+            let slug = if *n == 0 {
+                base
+            } else {
+                format!("{}-{}", base, n)
+            };
+            *n += 1;
+            Some(Heading {
+                level: caps[1].len(),
+                text,
+                slug,
+                line,
+            })
+        })
+        .collect()
+}
+
+/// Block ids (`^id` at the end of a line) with their line numbers.
+pub fn block_ids(content: &str) -> Vec<(String, usize)> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| Regex::new(r"(?:^|\s)\^([A-Za-z0-9-]+)\s*$").unwrap());
+    body_lines(content)
+        .filter_map(|(line, text)| re.captures(text).map(|c| (c[1].to_string(), line)))
+        .collect()
+}
+
+/// The 1-based line an anchor points to, if it exists in `content`.
+pub fn anchor_line(content: &str, anchor: &Anchor) -> Option<usize> {
+    match anchor {
+        Anchor::Heading(h) => {
+            let slug = heading_slug(h);
+            headings(content)
+                .into_iter()
+                .find(|hd| hd.slug == slug)
+                .map(|hd| hd.line)
+        }
+        Anchor::Block(id) => block_ids(content)
+            .into_iter()
+            .find(|(b, _)| b == id)
+            .map(|(_, line)| line),
+    }
+}
+
+/// The text an anchor points to: a heading with everything up to the next
+/// heading of the same or a higher level, or a block's line without its
+/// `^id` marker.
+pub fn anchor_section(content: &str, anchor: &Anchor) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    match anchor {
+        Anchor::Heading(h) => {
+            let slug = heading_slug(h);
+            let all = headings(content);
+            let pos = all.iter().position(|hd| hd.slug == slug)?;
+            let start = all[pos].line;
+            let end = all[pos + 1..]
+                .iter()
+                .find(|hd| hd.level <= all[pos].level)
+                .map_or(lines.len(), |hd| hd.line - 1);
+            Some(lines[start - 1..end].join("\n").trim_end().to_string())
+        }
+        Anchor::Block(id) => {
+            let line = anchor_line(content, anchor)?;
+            let text = lines[line - 1].trim_end();
+            let text = text[..text.len() - id.len() - 1].trim_end();
+            Some(text.to_string())
+        }
+    }
+}
+
+/// The sentence of `line` around the byte range `start..end`.
+fn sentence_at(line: &str, start: usize, end: usize) -> String {
+    // A sentence ends at `.`, `!` or `?` followed by whitespace or the end
+    // of the line, so `example.com` or `note.md` don't split it.
+    let is_end = |i: usize| {
+        matches!(line.as_bytes()[i], b'.' | b'!' | b'?')
+            && line
+                .as_bytes()
+                .get(i + 1)
+                .is_none_or(u8::is_ascii_whitespace)
+    };
+    let begin = (0..start).rev().find(|&i| is_end(i)).map_or(0, |i| i + 1);
+    let finish = (end..line.len())
+        .find(|&i| is_end(i))
+        .map_or(line.len(), |i| i + 1);
+    let sentence = line[begin..finish]
+        .trim()
+        .trim_start_matches(['-', '*', '>', '#'])
+        .trim();
+    if sentence.chars().count() > MAX_CONTEXT {
+        let cut: String = sentence.chars().take(MAX_CONTEXT).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        sentence.to_string()
+    }
+}
+
+/// A Markdown link href that points to a note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownTarget {
+    /// Normalized stem of the file; empty for `#heading` in the same note.
+    pub target: String,
+    /// The path part of the href, with `%20` decoded.
+    pub path: Option<String>,
+    pub anchor: Option<Anchor>,
+}
+
+/// The note a Markdown link points to, if it points to a note: a relative
+/// path ending in `.md` or without an extension, or `#heading` in the same
+/// note.
+pub fn markdown_target(href: &str) -> Option<MarkdownTarget> {
+    if href.contains("://") || href.starts_with("mailto:") {
+        return None;
+    }
+    let (path, fragment) = href.split_once('#').unwrap_or((href, ""));
+    let anchor = Anchor::parse(&fragment.replace("%20", " "));
+    if path.is_empty() {
+        return Some(MarkdownTarget {
+            target: String::new(),
+            path: None,
+            anchor,
+        });
+    }
+    let decoded = path.replace("%20", " ");
+    let file = Path::new(&decoded);
+    match file.extension().and_then(|e| e.to_str()) {
+        None | Some("md") => {}
+        Some(_) => return None,
+    }
+    let stem = file.file_stem()?.to_string_lossy();
+    Some(MarkdownTarget {
+        target: normalize_id(&stem),
+        anchor,
+        path: Some(decoded),
+    })
+}
+
+/// Resolve `.` and `..` in a path without touching the filesystem.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The file a Markdown link path points to: relative to the folder of the
+/// note at `from`, or to the vault root if it starts with `/`. Paths
+/// without an extension get `.md`.
+pub fn relative_note_path(from: &Path, href_path: &str) -> PathBuf {
+    let base = match href_path.strip_prefix('/') {
+        Some(rest) => notes::notes_dir().join(rest),
+        None => from.parent().unwrap_or(Path::new("")).join(href_path),
+    };
+    let mut path = normalize_path(&base);
+    if path.extension().is_none() {
+        path.set_extension("md");
+    }
+    path
+}
+
+/// The path to write in a Markdown link from the note at `from` to the
+/// note at `to`, relative to `from`'s folder, with spaces as `%20`.
+pub fn relative_href(from: &Path, to: &Path) -> String {
+    let dir: Vec<Component> = from
+        .parent()
+        .map(|d| d.components().collect())
+        .unwrap_or_default();
+    let to: Vec<Component> = to.components().collect();
+    let common = dir.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); dir.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+    parts.join("/").replace(' ', "%20")
+}
+
+/// The note a link target refers to, seen from the note at `from`. Markdown
+/// paths are resolved against `from`'s folder; bare file names that aren't
+/// found there are looked up across the vault, like wikilinks.
+pub fn resolve_target(target: &str, path: Option<&str>, from: &Path) -> Option<PathBuf> {
+    match path {
+        Some(rel) => {
+            let p = relative_note_path(from, rel);
+            if p.is_file() {
+                Some(p)
+            } else if !rel.contains('/') {
+                notes::resolve_note(target)
+            } else {
+                None
+            }
+        }
+        None if target.is_empty() => Some(from.to_path_buf()),
+        None => notes::resolve_note(target),
+    }
+}
+
+/// Extract every note reference from note content: `[[wikilinks]]`,
+/// `![[embeds]]`, `[text](note.md)` links in the body, and wikilinks in front
+/// matter values as relations. Links inside code are ignored.
+pub fn extract_links(content: &str) -> Vec<LinkRef> {
+    static RE_WIKI: OnceLock<Regex> = OnceLock::new();
+    static RE_MD: OnceLock<Regex> = OnceLock::new();
+    static RE_CODE: OnceLock<Regex> = OnceLock::new();
+    let re_wiki = RE_WIKI.get_or_init(|| Regex::new(r"(!?)\[\[(.+?)\]\]").unwrap());
+    let re_md = RE_MD
+        .get_or_init(|| Regex::new(r#"(!?)\[([^\]]*)\]\(([^)\s]+)(?:\s+"[^"]*")?\)"#).unwrap());
+    let re_code = RE_CODE.get_or_init(|| Regex::new(r"`[^`\n]*`").unwrap());
+
+    let mut out = Vec::new();
+    let (front, _) = notes::split_front_matter(content);
+
+    for (i, line) in front.unwrap_or_default().lines().enumerate() {
+        for caps in re_wiki.captures_iter(line) {
+            let link = parse_wikilink(&caps[2]);
+            out.push(LinkRef {
+                target: normalize_id(&link.target),
+                kind: LinkKind::Relation,
+                context: Some(line.trim().to_string()),
+                anchor: link.anchor,
+                label: link.label,
+                path: None,
+                raw: caps[0].to_string(),
+                // Line 1 is the opening `---`.
+                line: i + 2,
+            });
+        }
+    }
+
+    for (line_no, line) in body_lines(content) {
+        // Blank out inline code, keeping byte offsets for the context.
+        let line = re_code.replace_all(line, |c: &regex::Captures| " ".repeat(c[0].len()));
+
+        let mut found: Vec<(usize, LinkRef)> = Vec::new();
+        for caps in re_wiki.captures_iter(&line) {
+            let m = caps.get(0).unwrap();
+            let kind = if caps[1].is_empty() {
+                LinkKind::Wikilink
+            } else {
+                LinkKind::Embed
+            };
+            let link = parse_wikilink(&caps[2]);
+            found.push((
+                m.start(),
+                LinkRef {
+                    target: normalize_id(&link.target),
+                    kind,
+                    context: Some(sentence_at(&line, m.start(), m.end())),
+                    anchor: link.anchor,
+                    label: link.label,
+                    path: None,
+                    raw: m.as_str().to_string(),
+                    line: line_no,
+                },
+            ));
+        }
+        for caps in re_md.captures_iter(&line) {
+            let m = caps.get(0).unwrap();
+            if !caps[1].is_empty() {
+                continue;
+            }
+            if let Some(md) = markdown_target(&caps[3]) {
+                found.push((
+                    m.start(),
+                    LinkRef {
+                        target: md.target,
+                        kind: LinkKind::Markdown,
+                        context: Some(sentence_at(&line, m.start(), m.end())),
+                        anchor: md.anchor,
+                        label: Some(caps[2].to_string()).filter(|l| !l.is_empty()),
+                        path: md.path,
+                        raw: m.as_str().to_string(),
+                        line: line_no,
+                    },
+                ));
+            }
+        }
+        found.sort_by_key(|(pos, _)| *pos);
+        out.extend(found.into_iter().map(|(_, l)| l));
+    }
+    out
+}
+
+/// Where a link leads: the note and, for anchored links, the line.
+#[derive(Debug, Clone)]
+pub struct ResolvedLink {
+    pub stem: String,
+    pub path: PathBuf,
+    /// 1-based line of the heading or block the link points to.
+    pub line: Option<usize>,
+}
+
+/// Resolve a link found in the note at `from`. Errors describe why the link
+/// is broken.
+pub fn resolve(link: &LinkRef, from: &Path) -> Result<ResolvedLink, String> {
+    let path = resolve_target(&link.target, link.path.as_deref(), from).ok_or_else(|| {
+        format!(
+            "note '{}' does not exist",
+            link.path.as_deref().unwrap_or(&link.target)
+        )
+    })?;
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let line = match &link.anchor {
+        None => None,
+        Some(anchor) => {
+            let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            Some(anchor_line(&content, anchor).ok_or_else(|| match anchor {
+                Anchor::Heading(h) => format!("no heading '{}' in {}", h, stem),
+                Anchor::Block(id) => format!("no block ^{} in {}", id, stem),
+            })?)
+        }
+    };
+    Ok(ResolvedLink { stem, path, line })
+}
+
+/// A link that points to a missing note, heading or block.
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenLink {
+    /// Note path relative to the vault.
+    pub file: String,
+    pub line: usize,
+    pub link: String,
+    pub reason: String,
+}
+
+/// Check every link in the vault and return the broken ones.
+pub fn check_links() -> Vec<BrokenLink> {
+    let root = notes::notes_dir();
+    let mut broken = Vec::new();
+    for p in notes::walk_notes() {
+        let content = fs::read_to_string(&p).unwrap_or_default();
+        for link in extract_links(&content) {
+            if let Err(reason) = resolve(&link, &p) {
+                broken.push(BrokenLink {
+                    file: p
+                        .strip_prefix(&root)
+                        .unwrap_or(&p)
+                        .to_string_lossy()
+                        .to_string(),
+                    line: link.line,
+                    link: link.raw,
+                    reason,
+                });
+            }
+        }
+    }
+    broken
+}
+
+/// A link to a note from another note.
+#[derive(Debug, Clone, Serialize)]
+pub struct Backlink {
+    pub source: String,
+    /// Path of the linking note relative to the vault.
+    pub file: String,
+    pub line: usize,
+    pub kind: LinkKind,
+    pub context: Option<String>,
+    pub raw: String,
+}
+
+/// Every link to the note at `path` from other notes, wikilinks and
+/// Markdown links alike.
+pub fn backlinks(path: &Path) -> Vec<Backlink> {
+    let root = notes::notes_dir();
+    let stem = path
+        .file_stem()
+        .map(|s| normalize_id(&s.to_string_lossy()))
+        .unwrap_or_default();
+    let mut out = Vec::new();
+    for p in notes::walk_notes() {
+        if p == path {
+            continue;
+        }
+        let content = fs::read_to_string(&p).unwrap_or_default();
+        for link in extract_links(&content) {
+            if link.target != stem && link.path.is_none() {
+                continue;
+            }
+            if resolve_target(&link.target, link.path.as_deref(), &p).as_deref() != Some(path) {
+                continue;
+            }
+            out.push(Backlink {
+                source: p
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                file: p
+                    .strip_prefix(&root)
+                    .unwrap_or(&p)
+                    .to_string_lossy()
+                    .to_string(),
+                line: link.line,
+                kind: link.kind,
+                context: link.context,
+                raw: link.raw,
+            });
+        }
+    }
+    out
+}
+
+/// A link from the note at `from` to the note at `to` in the vault's
+/// configured [`LinkStyle`].
+pub fn format_link(from: &Path, to: &Path, label: Option<&str>) -> String {
+    let stem = to
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match config::load_config().link_style {
+        LinkStyle::Wikilink => match label {
+            Some(label) => format!("[[{}|{}]]", stem, label),
+            None => format!("[[{}]]", stem),
+        },
+        LinkStyle::Markdown => {
+            let text = match label {
+                Some(label) => label.to_string(),
+                None => fs::read_to_string(to)
+                    .ok()
+                    .and_then(|c| notes::parse_front_matter(&c).title)
+                    .unwrap_or_else(|| stem.replace('_', " ")),
+            };
+            format!("[{}]({})", text, relative_href(from, to))
+        }
+    }
+}
+
+/// `raw` wikilink or embed pointed at `stem`, keeping its anchor and label.
+fn retarget_wikilink(raw: &str, stem: &str) -> String {
+    let (bang, rest) = match raw.strip_prefix('!') {
+        Some(rest) => ("!", rest),
+        None => ("", raw),
+    };
+    let inner = &rest[2..rest.len() - 2];
+    let tail = inner.find(['#', '|']).map_or("", |i| &inner[i..]);
+    format!("{}[[{}{}]]", bang, stem, tail)
+}
+
+/// `raw` Markdown link with its file path replaced by `href`, keeping the
+/// fragment and title. Extensionless paths stay extensionless.
+fn retarget_markdown(raw: &str, old_path: &str, href: &str) -> String {
+    let Some(start) = raw.find("](").map(|i| i + 2) else {
+        return raw.to_string();
+    };
+    let end = raw[start..]
+        .find(['#', ')', ' '])
+        .map_or(raw.len(), |i| start + i);
+    let href = if Path::new(old_path).extension().is_none() {
+        href.trim_end_matches(".md")
+    } else {
+        href
+    };
+    format!("{}{}{}", &raw[..start], href, &raw[end..])
+}
+
+/// Move or rename a note, rewriting links to it across the vault and the
+/// relative Markdown links in the note itself. `dest` is a new name in the
+/// same folder, or a path from the vault root if it contains `/`. Returns
+/// the new path and the stems of the other notes that changed.
+pub fn move_note(stem: &str, dest: &str) -> io::Result<(PathBuf, Vec<String>)> {
+    let old = notes::resolve_note(stem).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, format!("Note not found: {}", stem))
+    })?;
+    let dest = dest.trim().trim_end_matches(".md");
+    if dest.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "New name is empty",
+        ));
+    }
+    let file_name = format!("{}.md", dest.trim_start_matches('/'));
+    let new = if dest.contains('/') {
+        let new = normalize_path(&notes::notes_dir().join(file_name));
+        if !new.starts_with(notes::notes_dir()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is outside the vault", new.display()),
+            ));
+        }
+        new
+    } else {
+        old.with_file_name(file_name)
+    };
+    if new.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", new.display()),
+        ));
+    }
+    let new_stem = new
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let renamed = old.file_stem() != new.file_stem();
+
+    let mut rewritten: Vec<(PathBuf, String)> = Vec::new();
+    for p in notes::walk_notes() {
+        let content = fs::read_to_string(&p)?;
+        // Where the note will live once the move is done.
+        let at = if p == old { new.as_path() } else { p.as_path() };
+        let mut edits: Vec<(usize, String, String)> = Vec::new();
+        for link in extract_links(&content) {
+            let Some(resolved) = resolve_target(&link.target, link.path.as_deref(), &p) else {
+                continue;
+            };
+            let points_here = resolved == old;
+            let replacement = match &link.path {
+                Some(rel) if points_here || p == old => {
+                    let target = if points_here { &new } else { &resolved };
+                    if relative_note_path(at, rel) == *target {
+                        continue;
+                    }
+                    retarget_markdown(&link.raw, rel, &relative_href(at, target))
+                }
+                None if points_here && renamed && !link.target.is_empty() => {
+                    retarget_wikilink(&link.raw, &new_stem)
+                }
+                _ => continue,
+            };
+            edits.push((link.line, link.raw, replacement));
+        }
+        if edits.is_empty() && p != old {
+            continue;
+        }
+        let mut lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+        for (line, raw, replacement) in edits {
+            if let Some(l) = lines.get_mut(line - 1) {
+                *l = l.replace(&raw, &replacement);
+            }
+        }
+        rewritten.push((p, lines.join("\n")));
+    }
+
+    let mut changed = Vec::new();
+    for (p, content) in rewritten {
+        if p == old {
+            if let Some(dir) = new.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&new, content)?;
+            fs::remove_file(&old)?;
+        } else {
+            fs::write(&p, content)?;
+            if let Some(s) = p.file_stem() {
+                changed.push(s.to_string_lossy().to_string());
+            }
+        }
+    }
+    // Notes hidden by `.nrsignore` aren't scanned above but still move.
+    if old.exists() {
+        if let Some(dir) = new.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::rename(&old, &new)?;
+    }
+    changed.sort();
+    Ok((new, changed))
+}
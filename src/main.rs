@@ -1,12 +1,30 @@
 pub mod ai;
+pub mod analytics;
 pub mod commands;
+pub mod completions;
+pub mod config;
+pub mod export;
+pub mod graph;
+pub mod links;
 pub mod notes;
+pub mod periodic;
+pub mod render;
+pub mod search;
+pub mod semantic;
+pub mod tags;
+pub mod templates;
 pub mod tui;
 pub mod web;
 
-use clap::{Parser, Subcommand};
+use chrono::{Local, NaiveDate, TimeZone};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use config::ConflictPolicy;
+use notes::NoteError;
+use periodic::Period;
+use std::collections::HashMap;
 use std::fs;
-use std::io;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 
 pub type MyError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
@@ -24,7 +42,40 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Create a new note
-    New { title: String },
+    New {
+        title: String,
+        /// Template from ~/notes/templates to fill in
+        #[arg(short, long)]
+        template: Option<String>,
+        /// Value for a custom template field; missing fields are prompted for
+        #[arg(long = "var", value_name = "NAME=VALUE")]
+        vars: Vec<String>,
+        /// What to do if the file name is taken (default: the vault's
+        /// slug.on_conflict setting)
+        #[arg(long, value_enum)]
+        on_conflict: Option<ConflictPolicy>,
+        /// Read the note body from stdin
+        #[arg(long)]
+        stdin: bool,
+    },
+    /// Print the path of today's daily note, creating it if needed
+    Today,
+    /// Print the path of the daily note for a date, creating it if needed
+    Daily {
+        /// YYYY-MM-DD, today, yesterday or tomorrow
+        #[arg(short, long, default_value = "today", value_parser = periodic::parse_date)]
+        date: NaiveDate,
+    },
+    /// Print the path of the weekly note for the week of a date
+    Weekly {
+        #[arg(short, long, default_value = "today", value_parser = periodic::parse_date)]
+        date: NaiveDate,
+    },
+    /// Print the path of the monthly note for the month of a date
+    Monthly {
+        #[arg(short, long, default_value = "today", value_parser = periodic::parse_date)]
+        date: NaiveDate,
+    },
     /// Run TUI
     Tui,
     /// Start the web server
@@ -32,6 +83,355 @@ enum Commands {
         #[arg(short, long, default_value_t = 4321)]
         port: u16,
     },
+    /// List notes, newest first
+    List {
+        #[arg(short, long, value_enum, default_value_t = ListSort::Modified)]
+        sort: ListSort,
+        /// Reverse the sort order
+        #[arg(short, long)]
+        reverse: bool,
+        /// Only notes with this tag (or a child tag); repeat for several
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Only notes under this folder of the vault
+        #[arg(long)]
+        folder: Option<String>,
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Print a note (styled on a terminal, raw when piped)
+    Show {
+        note: String,
+        #[arg(short, long, value_enum)]
+        format: Option<ShowFormat>,
+    },
+    /// Open a note in $EDITOR
+    Edit { note: String },
+    /// Print the file path of a note
+    Path { note: String },
+    /// Print a shell completion script, e.g. `source <(nrs completions bash)`
+    Completions {
+        #[arg(value_enum)]
+        shell: completions::CompletionShell,
+    },
+    /// List note slugs or tags, one per line, for shell completion
+    #[command(hide = true)]
+    Complete {
+        #[arg(value_enum)]
+        kind: completions::Candidates,
+    },
+    /// Search notes with a query, or by meaning with --semantic
+    Search {
+        query: String,
+        /// Rank notes by similarity using offline embeddings
+        #[arg(long)]
+        semantic: bool,
+        /// Maximum number of results
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+        /// Print results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search the vault with a regular expression
+    Grep {
+        pattern: String,
+        /// Match case-insensitively
+        #[arg(short, long, overrides_with_all = ["case_sensitive", "smart_case"])]
+        ignore_case: bool,
+        /// Match case-sensitively (the default)
+        #[arg(short = 's', long, overrides_with_all = ["ignore_case", "smart_case"])]
+        case_sensitive: bool,
+        /// Match case-insensitively unless the pattern has uppercase letters
+        #[arg(short = 'S', long, overrides_with_all = ["ignore_case", "case_sensitive"])]
+        smart_case: bool,
+        /// Print matches as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check that links point to existing notes, headings and blocks
+    Check {
+        /// Print broken links as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the notes linking to a note
+    Backlinks {
+        note: String,
+        /// Print backlinks as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Append a link to another note, in the vault's link style
+    Link {
+        note: String,
+        target: String,
+        /// Display text for the link
+        #[arg(short, long)]
+        label: Option<String>,
+    },
+    /// Append text (or stdin) to the end of a note
+    Append {
+        note: String,
+        /// Text to append; read from stdin if omitted
+        text: Vec<String>,
+        /// Put the text under a `## YYYY-MM-DD HH:MM` heading
+        #[arg(short, long)]
+        timestamp: bool,
+    },
+    /// Append timestamped text (or stdin) to the inbox note
+    Capture {
+        /// Text to capture; read from stdin if omitted
+        text: Vec<String>,
+    },
+    /// Rename or move a note and update links to it
+    Mv {
+        note: String,
+        /// New name, or a path from the vault root such as `projects/new`
+        dest: String,
+    },
+    /// Render a note to HTML, or to Markdown with embeds expanded
+    Render {
+        note: String,
+        /// Output a single Markdown file with all `![[embeds]]` inlined
+        #[arg(long)]
+        flatten: bool,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Export or inspect the note graph
+    Graph {
+        #[command(subcommand)]
+        action: GraphAction,
+    },
+    /// Rename, merge, add or remove tags in note front matter
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum GraphAction {
+    /// Write the graph with node attributes and edge types
+    Export {
+        #[arg(short, long, value_enum, default_value_t = export::GraphFormat::Json)]
+        format: export::GraphFormat,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Add PageRank, betweenness and community to every node
+        #[arg(long)]
+        centrality: bool,
+    },
+    /// Show hub notes by centrality and the communities of the graph
+    Stats {
+        /// Number of notes and communities to list
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+        /// Leave tag nodes out of the graph
+        #[arg(long)]
+        no_tags: bool,
+        /// Print all metrics as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Find the shortest chain of links between two notes
+    Path {
+        from: String,
+        to: String,
+        /// Only follow note links, not shared tags
+        #[arg(long)]
+        no_tags: bool,
+        /// Print the path as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TagAction {
+    /// Rename a tag, including its nested tags, across the vault
+    Rename { old: String, new: String },
+    /// Merge tags into one tag across the vault
+    Merge {
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// The tag to merge into
+        #[arg(long)]
+        into: String,
+    },
+    /// Add a tag to notes
+    Add {
+        tag: String,
+        #[arg(required = true)]
+        notes: Vec<String>,
+    },
+    /// Remove a tag from notes
+    Remove {
+        tag: String,
+        #[arg(required = true)]
+        notes: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ListSort {
+    /// Last modified, newest first
+    Modified,
+    /// File name
+    Name,
+    /// Title, case-insensitive
+    Title,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Table,
+    /// A JSON array
+    Json,
+    /// One JSON object per line
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ShowFormat {
+    /// The file as written
+    Raw,
+    /// Styled for the terminal, with embeds expanded
+    Rendered,
+    /// Title, tags, preview and modification time as JSON
+    Json,
+}
+
+/// The path of a note given on the command line; exits with status 2 if
+/// there is no such note.
+fn find_note(note: &str) -> PathBuf {
+    notes::resolve_note(note).unwrap_or_else(|| {
+        eprintln!("Note not found: {}", note);
+        std::process::exit(2);
+    })
+}
+
+/// Print notes as an aligned table of modification time, name, title and
+/// tags.
+fn print_note_table(notes: &[notes::NoteSummary]) {
+    let width = |f: fn(&notes::NoteSummary) -> usize| notes.iter().map(f).max().unwrap_or(0);
+    let slug_w = width(|n| n.slug.chars().count());
+    let title_w = width(|n| n.title.chars().count());
+    for n in notes {
+        let modified = Local
+            .timestamp_opt(n.last_modified as i64, 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let tags: Vec<String> = n.tags.iter().map(|t| format!("#{}", t)).collect();
+        let line = format!(
+            "{:<16}  {:<slug_w$}  {:<title_w$}  {}",
+            modified,
+            n.slug,
+            n.title,
+            tags.join(" ")
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Print the path of a periodic note, creating it if needed.
+fn open_periodic(period: Period, date: NaiveDate) {
+    match periodic::open_periodic(period, date) {
+        Ok((path, created)) => {
+            if created {
+                eprintln!("Created note at: {}", path.display());
+            }
+            println!("{}", path.display());
+        }
+        Err(e) => {
+            eprintln!("Error opening {} note: {}", period.label(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Text read from stdin up to EOF, or `None` if it is empty.
+fn read_stdin() -> io::Result<Option<String>> {
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+    Ok((!text.trim().is_empty()).then_some(text))
+}
+
+/// Text piped on stdin, or `None` if stdin is a terminal or empty.
+fn piped_stdin() -> io::Result<Option<String>> {
+    if io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    read_stdin()
+}
+
+/// Append the words given on the command line, or stdin, to a note.
+fn append_text(path: &Path, words: &[String], timestamp: bool) {
+    let text = if words.is_empty() {
+        match piped_stdin() {
+            Ok(Some(text)) => text,
+            Ok(None) => {
+                eprintln!("Nothing to append: give the text or pipe it on stdin");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Error reading stdin: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        words.join(" ")
+    };
+    match notes::append_to_note(path, &text, timestamp) {
+        Ok(()) => println!("Appended to {}", path.display()),
+        Err(e) => {
+            eprintln!("Error appending to {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Custom template field values from `--var name=value`, prompting on the
+/// terminal for fields the template uses but that weren't given.
+fn template_fields(template: Option<&str>, vars: &[String]) -> io::Result<HashMap<String, String>> {
+    let mut fields = HashMap::new();
+    for var in vars {
+        let (name, value) = var.split_once('=').ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Expected NAME=VALUE, got '{}'", var),
+            )
+        })?;
+        fields.insert(name.trim().to_lowercase(), value.to_string());
+    }
+    let text = match template {
+        Some(name) => templates::load_template(name)?,
+        None => templates::load_template(templates::DEFAULT_TEMPLATE).unwrap_or_default(),
+    };
+    for field in templates::template_fields(&text) {
+        if fields.contains_key(&field) {
+            continue;
+        }
+        if !io::stdin().is_terminal() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Missing template field '{}' (use --var {}=...)",
+                    field, field
+                ),
+            ));
+        }
+        print!("{}: ", field);
+        io::stdout().flush()?;
+        let mut value = String::new();
+        io::stdin().read_line(&mut value)?;
+        fields.insert(field, value.trim_end_matches(['\r', '\n']).to_string());
+    }
+    Ok(fields)
 }
 
 #[tokio::main]
@@ -47,12 +447,144 @@ async fn main() -> io::Result<()> {
         })?;
     }
 
+    // Report an invalid config once; commands fall back to the defaults.
+    // The TUI shows it in its status bar instead.
+    if !matches!(cli.command, Commands::Tui) {
+        if let Err(e) = config::try_load_config() {
+            eprintln!("{}", e);
+        }
+    }
+
     match cli.command {
-        Commands::New { title } => {
-            if let Err(e) = notes::create_new_note(&title) {
-                eprintln!("Error creating note: {}", e);
+        Commands::New {
+            title,
+            template,
+            vars,
+            on_conflict,
+            stdin,
+        } => {
+            let body = if stdin { read_stdin() } else { Ok(None) };
+            let result = body
+                .and_then(|body| Ok((body, template_fields(template.as_deref(), &vars)?)))
+                .map_err(NoteError::from)
+                .and_then(|(body, fields)| {
+                    notes::create_note(
+                        &title,
+                        template.as_deref(),
+                        &fields,
+                        body.as_deref(),
+                        on_conflict,
+                    )
+                });
+            match result {
+                Ok((path, true)) => println!("Created note at: {}", path.display()),
+                Ok((path, false)) => println!("Opened existing note at: {}", path.display()),
+                Err(e) => {
+                    eprintln!("Error creating note: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Today => open_periodic(Period::Daily, Local::now().date_naive()),
+        Commands::Daily { date } => open_periodic(Period::Daily, date),
+        Commands::Weekly { date } => open_periodic(Period::Weekly, date),
+        Commands::Monthly { date } => open_periodic(Period::Monthly, date),
+        Commands::List {
+            sort,
+            reverse,
+            tags,
+            folder,
+            format,
+        } => {
+            let folder = folder.map(|f| format!("{}/", f.trim_matches('/')));
+            let mut notes: Vec<notes::NoteSummary> = notes::note_summaries()
+                .into_iter()
+                .filter(|n| {
+                    tags.iter()
+                        .all(|q| n.tags.iter().any(|t| tags::tag_matches(q, t)))
+                })
+                .filter(|n| {
+                    folder
+                        .as_ref()
+                        .is_none_or(|f| n.path.starts_with(f.as_str()))
+                })
+                .collect();
+            match sort {
+                ListSort::Modified => notes.sort_by_key(|n| std::cmp::Reverse(n.last_modified)),
+                ListSort::Name => notes.sort_by(|a, b| a.slug.cmp(&b.slug)),
+                ListSort::Title => {
+                    notes.sort_by_key(|n| n.title.to_lowercase());
+                }
+            }
+            if reverse {
+                notes.reverse();
             }
+            match format {
+                OutputFormat::Table => print_note_table(&notes),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&notes)?),
+                OutputFormat::Ndjson => {
+                    for n in &notes {
+                        println!("{}", serde_json::to_string(n)?);
+                    }
+                }
+            }
+        }
+        Commands::Show { note, format } => {
+            let path = find_note(&note);
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("Error reading {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            let format = format.unwrap_or(if io::stdout().is_terminal() {
+                ShowFormat::Rendered
+            } else {
+                ShowFormat::Raw
+            });
+            match format {
+                ShowFormat::Raw => print!("{}", content),
+                ShowFormat::Rendered => println!("{}", render::render_terminal(&content, &path)),
+                ShowFormat::Json => {
+                    let slug = path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let Some(data) = web::extract_note_data_at(&slug, &path) else {
+                        eprintln!("Error reading {}", path.display());
+                        std::process::exit(1);
+                    };
+                    println!("{}", serde_json::to_string_pretty(&data)?);
+                }
+            }
+        }
+        Commands::Edit { note } => {
+            let path = find_note(&note);
+            match tui::spawn_editor(&path) {
+                Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+                Err(e) => {
+                    eprintln!("Error starting editor: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Path { note } => println!("{}", find_note(&note).display()),
+        Commands::Completions { shell } => {
+            completions::write_completions(shell, &mut Cli::command(), &mut io::stdout());
         }
+        Commands::Complete { kind } => match kind {
+            completions::Candidates::Notes => {
+                for stem in notes::load_notes_list() {
+                    println!("{}", stem);
+                }
+            }
+            completions::Candidates::Tags => {
+                for count in tags::tag_counts(&graph::scan_vault()) {
+                    println!("{}", count.tag);
+                }
+            }
+        },
         Commands::Tui => {
             if let Err(e) = tui::run_tui() {
                 eprintln!("Error in TUI: {}", e);
@@ -61,6 +593,247 @@ async fn main() -> io::Result<()> {
         Commands::Serve { port } => {
             web::serve_notes(port).await?;
         }
+        Commands::Search {
+            query,
+            semantic,
+            limit,
+            json,
+        } => {
+            let hits: Vec<(String, Option<f32>)> = if semantic {
+                let embedder = semantic::default_embedder();
+                let index = semantic::VectorIndex::load_or_build(embedder.as_ref())?;
+                index
+                    .search(embedder.as_ref(), &query, limit)
+                    .into_iter()
+                    .map(|(stem, score)| (stem, Some(score)))
+                    .collect()
+            } else {
+                search::run_query(&search::Query::parse(&query))
+                    .into_iter()
+                    .take(limit)
+                    .map(|stem| (stem, None))
+                    .collect()
+            };
+            if json {
+                let out: Vec<_> = hits
+                    .iter()
+                    .map(|(stem, score)| serde_json::json!({ "slug": stem, "score": score }))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&out)?);
+            } else {
+                for (stem, score) in &hits {
+                    match score {
+                        Some(score) => println!("{:.3}  {}", score, stem),
+                        None => println!("{}", stem),
+                    }
+                }
+            }
+            if hits.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Grep {
+            pattern,
+            ignore_case,
+            case_sensitive,
+            smart_case,
+            json,
+        } => {
+            // The flags override each other, so the last one given wins.
+            let case = if case_sensitive {
+                search::CaseMode::Sensitive
+            } else if ignore_case {
+                search::CaseMode::Insensitive
+            } else if smart_case {
+                search::CaseMode::Smart
+            } else {
+                search::CaseMode::Sensitive
+            };
+            let re = match search::build_regex(&pattern, case) {
+                Ok(re) => re,
+                Err(e) => {
+                    eprintln!("Invalid pattern: {}", e);
+                    std::process::exit(2);
+                }
+            };
+            let matches = search::grep(&re);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&matches)?);
+            } else {
+                for m in &matches {
+                    println!("{}:{}:{}:{}", m.file, m.line, m.column, m.text);
+                }
+            }
+            if matches.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Check { json } => {
+            let broken = links::check_links();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&broken)?);
+            } else {
+                for b in &broken {
+                    println!("{}:{}: {} ({})", b.file, b.line, b.link, b.reason);
+                }
+            }
+            if !broken.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Backlinks { note, json } => {
+            let path = find_note(&note);
+            let backlinks = links::backlinks(&path);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&backlinks)?);
+            } else {
+                for b in &backlinks {
+                    println!("{}:{}: {}", b.file, b.line, b.raw);
+                }
+            }
+        }
+        Commands::Link {
+            note,
+            target,
+            label,
+        } => {
+            let (from, to) = (find_note(&note), find_note(&target));
+            let link = links::format_link(&from, &to, label.as_deref());
+            let mut content = fs::read_to_string(&from)?;
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(&link);
+            content.push('\n');
+            fs::write(&from, content)?;
+            println!("{}", link);
+        }
+        Commands::Append {
+            note,
+            text,
+            timestamp,
+        } => {
+            let path = find_note(&note);
+            append_text(&path, &text, timestamp);
+        }
+        Commands::Capture { text } => match notes::inbox_note() {
+            Ok(path) => append_text(&path, &text, true),
+            Err(e) => {
+                eprintln!("Error opening inbox: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Mv { note, dest } => match links::move_note(&note, &dest) {
+            Ok((path, changed)) => {
+                println!("Moved {} -> {}", note, path.display());
+                println!("Updated {} note(s)", changed.len());
+                for stem in &changed {
+                    println!("  {}", stem);
+                }
+            }
+            Err(e) => {
+                eprintln!("Error moving note: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Render {
+            note,
+            flatten,
+            output,
+        } => {
+            let path = find_note(&note);
+            let content = fs::read_to_string(&path)?;
+            let rendered = if flatten {
+                render::flatten(&content, &path)
+            } else {
+                render::render_html(&content, &path)
+            };
+            match output {
+                Some(out) => fs::write(out, rendered)?,
+                None => print!("{}", rendered),
+            }
+        }
+        Commands::Graph { action } => match action {
+            GraphAction::Export {
+                format,
+                output,
+                centrality,
+            } => {
+                let rendered = export::ExportGraph::from_notes(&graph::scan_vault(), centrality)
+                    .render(format);
+                match output {
+                    Some(path) => fs::write(path, rendered)?,
+                    None => print!("{}", rendered),
+                }
+            }
+            GraphAction::Stats {
+                limit,
+                no_tags,
+                json,
+            } => {
+                let notes = graph::scan_vault();
+                let (nodes, links) = graph::graph_from_notes(&notes);
+                let filter = graph::GraphFilter {
+                    hide_tags: no_tags,
+                    ..Default::default()
+                };
+                let (nodes, links) = graph::filter_graph(nodes, links, &notes, &filter);
+                let stats = analytics::analyze(&nodes, &links);
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&stats)?);
+                } else {
+                    print!("{}", stats.summary(limit));
+                }
+            }
+            GraphAction::Path {
+                from,
+                to,
+                no_tags,
+                json,
+            } => {
+                let (nodes, links) = graph::build_graph();
+                let [start, goal] = [&from, &to].map(|stem| {
+                    graph::note_node_id(&nodes, stem).unwrap_or_else(|| {
+                        eprintln!("Note not found: {}", stem);
+                        std::process::exit(2);
+                    })
+                });
+                let path = analytics::shortest_path(&nodes, &links, &start, &goal, !no_tags);
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&path)?);
+                } else if let Some(path) = &path {
+                    println!("{}", path.join(" -> "));
+                } else {
+                    eprintln!("No path between {} and {}", from, to);
+                }
+                if path.is_none() {
+                    std::process::exit(1);
+                }
+            }
+        },
+        Commands::Tag { action } => {
+            let op = match action {
+                TagAction::Rename { old, new } => tags::TagOp::Rename { from: old, to: new },
+                TagAction::Merge { tags, into } => tags::TagOp::Merge {
+                    sources: tags,
+                    into,
+                },
+                TagAction::Add { tag, notes } => tags::TagOp::Add { tag, notes },
+                TagAction::Remove { tag, notes } => tags::TagOp::Remove { tag, notes },
+            };
+            match tags::apply_tag_op(&op) {
+                Ok(changed) => {
+                    println!("Updated {} note(s)", changed.len());
+                    for stem in &changed {
+                        println!("  {}", stem);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error updating tags: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
     }
 
     Ok(())
@@ -1,9 +1,19 @@
+use crate::config::{self, ConflictPolicy, SlugSettings};
+use crate::tags;
+use crate::templates;
+use chrono::Local;
 use dirs::home_dir;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// Name of the ignore file in the vault root (one glob pattern per line).
+pub const IGNORE_FILE: &str = ".nrsignore";
+
 /// Return the path to `~/notes`.
 pub fn notes_dir() -> PathBuf {
     home_dir()
@@ -11,53 +21,560 @@ pub fn notes_dir() -> PathBuf {
         .join("notes")
 }
 
+/// A note's path from the vault root, e.g. `projects/plan.md`.
+pub fn relative_path(path: &Path) -> String {
+    path.strip_prefix(notes_dir())
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
 /// Build a `.md` path for the given stem in `~/notes`.
 pub fn note_path(stem: &str) -> PathBuf {
     notes_dir().join(format!("{}.md", stem))
 }
 
-/// Create a new note with YAML front matter.
-pub fn create_new_note(title: &str) -> io::Result<()> {
-    let slug = title
-        .to_lowercase()
-        .replace(' ', "_")
-        .replace("/", "_")
-        .replace("\\", "_");
+/// Path of every note by stem, from a single walk of the vault. If a stem
+/// is used in several folders, the note nearest the root wins.
+pub fn note_paths() -> HashMap<String, PathBuf> {
+    let mut paths = walk_notes();
+    paths.sort_by_key(|p| (p.components().count(), p.clone()));
+    let mut out = HashMap::new();
+    for p in paths {
+        if let Some(stem) = p.file_stem() {
+            out.entry(stem.to_string_lossy().to_string()).or_insert(p);
+        }
+    }
+    out
+}
+
+/// Find the file of the note with the given stem, in the vault root or a
+/// subfolder. Falls back to the root path if there is none. This walks the
+/// vault, so use [`note_paths`] to look up many notes.
+pub fn find_note_path(stem: &str) -> PathBuf {
+    let root_path = note_path(stem);
+    if root_path.exists() {
+        return root_path;
+    }
+    walk_notes()
+        .into_iter()
+        .find(|p| p.file_stem().is_some_and(|s| s == stem))
+        .unwrap_or(root_path)
+}
+
+/// Find the note a link points to. Link targets match note stems
+/// case-insensitively, with spaces and underscores treated alike.
+pub fn resolve_note(target: &str) -> Option<PathBuf> {
+    let root_path = note_path(target);
+    if root_path.exists() {
+        return Some(root_path);
+    }
+    let want = crate::graph::normalize_id(target);
+    let paths = walk_notes();
+    paths
+        .iter()
+        .find(|p| p.file_stem().is_some_and(|s| s == target))
+        .or_else(|| {
+            paths.iter().find(|p| {
+                p.file_stem()
+                    .is_some_and(|s| crate::graph::normalize_id(&s.to_string_lossy()) == want)
+            })
+        })
+        .cloned()
+}
+
+/// Why a note could not be created.
+#[derive(Debug)]
+pub enum NoteError {
+    /// The title has no characters usable in a file name.
+    EmptyTitle,
+    /// A note with the same file name exists.
+    Exists(PathBuf),
+    TemplateNotFound(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for NoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoteError::EmptyTitle => write!(f, "Note title is empty"),
+            NoteError::Exists(path) => write!(f, "Note already exists: {}", path.display()),
+            NoteError::TemplateNotFound(name) => write!(f, "Template not found: {}", name),
+            NoteError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for NoteError {}
+
+impl From<io::Error> for NoteError {
+    fn from(e: io::Error) -> Self {
+        NoteError::Io(e)
+    }
+}
+
+/// Characters that can't appear in a file name on some platform.
+fn is_unsafe_in_filename(c: char) -> bool {
+    c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+}
+
+/// Turn a title into a file name: transliterated to ASCII, lowercased, with
+/// runs of spaces and punctuation replaced by the separator. A single `-` or
+/// `_` between words is kept. The result is cut to `max_length` characters
+/// (0 for no limit) and never starts or ends with punctuation.
+pub fn slugify(title: &str, settings: &SlugSettings) -> String {
+    let sep: String = settings
+        .separator
+        .chars()
+        .filter(|c| !is_unsafe_in_filename(*c) && *c != '.')
+        .collect();
+    let mut slug = String::new();
+    let mut gap = String::new();
+    for c in deunicode::deunicode(title).to_lowercase().chars() {
+        if !c.is_ascii_alphanumeric() {
+            gap.push(c);
+            continue;
+        }
+        if !slug.is_empty() && !gap.is_empty() {
+            slug.push_str(if gap == "-" || gap == "_" { &gap } else { &sep });
+        }
+        gap.clear();
+        slug.push(c);
+    }
+    if settings.max_length > 0 {
+        if let Some((end, _)) = slug.char_indices().nth(settings.max_length) {
+            slug.truncate(end);
+        }
+    }
+    slug.trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_string()
+}
+
+/// File name for a note title, using the vault's slug settings.
+pub fn title_slug(title: &str) -> String {
+    slugify(title, &config::load_config().slug)
+}
+
+/// Create a note from a template, or from the `default` template or the
+/// built-in layout when none is given. Templates without front matter get
+/// `title` and `tags`. A `body` replaces the placeholder text of the
+/// built-in layout, or is added after the filled template. If the file
+/// name is taken, `on_conflict` (or the vault's `slug.on_conflict` setting)
+/// decides between failing, a numbered file name and returning the existing
+/// note. Returns the note's path and whether it was created.
+pub fn create_note(
+    title: &str,
+    template: Option<&str>,
+    fields: &HashMap<String, String>,
+    body: Option<&str>,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<(PathBuf, bool), NoteError> {
+    let settings = config::load_config().slug;
+    let mut slug = slugify(title, &settings);
+    if slug.is_empty() {
+        return Err(NoteError::EmptyTitle);
+    }
     let path = note_path(&slug);
     if path.exists() {
-        eprintln!("Note already exists: {}", path.display());
-        return Ok(());
+        match on_conflict.unwrap_or(settings.on_conflict) {
+            ConflictPolicy::Error => return Err(NoteError::Exists(path)),
+            ConflictPolicy::Open => return Ok((path, false)),
+            ConflictPolicy::Suffix => {
+                slug = (2..)
+                    .map(|n| format!("{}-{}", slug, n))
+                    .find(|s| !note_path(s).exists())
+                    .unwrap_or(slug);
+            }
+        }
     }
-    let mut f = fs::File::create(&path)?;
-    let content = format!(
-        r#"---
-title: {t}
+    let path = note_path(&slug);
+    let template = match template {
+        Some(name) => Some(templates::load_template(name).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => NoteError::TemplateNotFound(name.to_string()),
+            _ => NoteError::Io(e),
+        })?),
+        None => templates::load_template(templates::DEFAULT_TEMPLATE).ok(),
+    };
+    let content = match template {
+        Some(t) => {
+            let filled = templates::fill_template(&t, title, &slug, fields);
+            let filled = match body {
+                Some(b) => format!("{}\n\n{}\n", filled.trim_end(), b.trim_end()),
+                None => filled,
+            };
+            if split_front_matter(&filled).0.is_some() {
+                filled
+            } else {
+                format!(
+                    "---\ntitle: {}\ntags: []\n---\n{}",
+                    yaml_scalar(title),
+                    filled
+                )
+            }
+        }
+        None => format!(
+            r#"---
+title: {q}
 tags: []
 ---
 # {t}
 
-Write your note here.
+{b}
 "#,
-        t = title
-    );
+            q = yaml_scalar(title),
+            t = title,
+            b = body.map(str::trim_end).unwrap_or("Write your note here.")
+        ),
+    };
+    let mut f = fs::File::create(&path)?;
     f.write_all(content.as_bytes())?;
-    println!("Created note at: {}", path.display());
-    Ok(())
+    Ok((path, true))
 }
 
-/// Load and return a sorted list of note stems.
-pub fn load_notes_list() -> Vec<String> {
-    let mut out = Vec::new();
-    for entry in WalkDir::new(notes_dir()).into_iter().filter_map(Result::ok) {
-        if entry.file_type().is_file() {
-            let p = entry.path();
-            if p.extension().and_then(|s| s.to_str()) == Some("md") {
-                if let Some(stem) = p.file_stem() {
-                    out.push(stem.to_string_lossy().to_string());
-                }
+/// Append text to the end of a note, under a `## YYYY-MM-DD HH:MM` heading
+/// if `timestamp` is set, and set its `updated` front matter field.
+pub fn append_to_note(path: &Path, text: &str, timestamp: bool) -> io::Result<()> {
+    let now = Local::now();
+    let content = fs::read_to_string(path)?;
+    let mut content = set_front_matter_field(
+        &content,
+        "updated",
+        &now.format("%Y-%m-%d %H:%M").to_string(),
+    );
+    let content_end = content.trim_end().len();
+    content.truncate(content_end);
+    content.push_str("\n\n");
+    if timestamp {
+        content.push_str(&format!("## {}\n\n", now.format("%Y-%m-%d %H:%M")));
+    }
+    content.push_str(text.trim_end());
+    content.push('\n');
+    fs::write(path, content)
+}
+
+/// The inbox note used by `nrs capture`, created if it doesn't exist.
+pub fn inbox_note() -> Result<PathBuf, NoteError> {
+    let config = config::load_config();
+    let name = config.inbox.as_deref().unwrap_or(config::DEFAULT_INBOX);
+    if let Some(path) = resolve_note(name) {
+        return Ok(path);
+    }
+    create_note(
+        name,
+        None,
+        &HashMap::new(),
+        Some(""),
+        Some(ConflictPolicy::Open),
+    )
+    .map(|(path, _)| path)
+}
+
+/// Metadata parsed from a note's YAML front matter.
+#[derive(Debug, Default, Clone)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    /// Every other `key: value` pair, keyed by lowercase key.
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Split a note into its front matter block (without delimiters) and body.
+pub fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    let is_delim = |l: &str| l.trim_end() == "---";
+    let first_end = content.find('\n').unwrap_or(content.len());
+    if !is_delim(&content[..first_end]) {
+        return (None, content);
+    }
+    let rest = &content[(first_end + 1).min(content.len())..];
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if is_delim(line.trim_end_matches('\n')) {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// Set a top-level `key: value` entry in a note's front matter, replacing
+/// the existing entry (including a block list below it) and leaving every
+/// other line untouched. Adds a front matter block if the note has none.
+pub fn set_front_matter_field(content: &str, key: &str, value: &str) -> String {
+    set_front_matter_entry(content, key, &format!("{}: {}", key, value))
+}
+
+/// Like [`set_front_matter_field`], with the entry's full text, which may
+/// span several lines.
+pub fn set_front_matter_entry(content: &str, key: &str, entry: &str) -> String {
+    let (Some(block), body) = split_front_matter(content) else {
+        return format!("---\n{}\n---\n{}", entry, content);
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut replaced = false;
+    let mut in_list = false;
+    for line in block.lines() {
+        if in_list {
+            if line.trim_start().starts_with("- ") || line.trim().is_empty() {
+                continue;
+            }
+            in_list = false;
+        }
+        let k = line.split_once(':').map(|(k, _)| k.trim());
+        if k == Some(key) && !line.starts_with(char::is_whitespace) {
+            lines.push(entry.to_string());
+            replaced = true;
+            in_list = line
+                .split_once(':')
+                .is_some_and(|(_, v)| v.trim().is_empty());
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if !replaced {
+        lines.push(entry.to_string());
+    }
+    format!("---\n{}\n---\n{}", lines.join("\n"), body)
+}
+
+/// A string as a YAML scalar, double-quoted if it would otherwise be read
+/// as something else (a mapping, comment, list, ...).
+pub fn yaml_scalar(s: &str) -> String {
+    quote_scalar(s, false)
+}
+
+/// A string as an item of an inline YAML list (`[a, b]`), where `,`, `[`,
+/// `]`, `{` and `}` need quoting too.
+pub fn yaml_flow_scalar(s: &str) -> String {
+    quote_scalar(s, true)
+}
+
+fn quote_scalar(s: &str, in_flow: bool) -> String {
+    let plain = !s.is_empty()
+        && s.trim() == s
+        && !s.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.ends_with(':')
+        && !(in_flow && s.contains([',', '[', ']', '{', '}']));
+    if plain {
+        s.to_string()
+    } else {
+        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Read a YAML scalar written plain, single- or double-quoted.
+fn unquote_scalar(value: &str) -> String {
+    let value = value.trim();
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => out.extend(chars.next()),
+                c => out.push(c),
+            }
+        }
+        out
+    } else if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        inner.replace("''", "'")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Parse a YAML list value, either inline (`[a, b]`) or a bare scalar.
+fn parse_inline_list(value: &str) -> Vec<String> {
+    let inner = value.trim().trim_start_matches('[').trim_end_matches(']');
+    // Split on commas outside quotes.
+    let mut items = vec![String::new()];
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in inner.chars() {
+        match (quote, c) {
+            _ if escaped => escaped = false,
+            (Some('"'), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, ',') => {
+                items.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(item) = items.last_mut() {
+            item.push(c);
+        }
+    }
+    items
+        .iter()
+        .map(|s| unquote_scalar(s))
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Parse the front matter of a note. Supports `key: value`, inline lists
+/// (`tags: [a, b]`) and block lists (`- item` lines under a key).
+pub fn parse_front_matter(content: &str) -> FrontMatter {
+    let mut fm = FrontMatter::default();
+    let Some(block) = split_front_matter(content).0 else {
+        return fm;
+    };
+
+    let mut current_key = String::new();
+    for line in block.lines() {
+        let trimmed = line.trim();
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            let item = unquote_scalar(item);
+            match current_key.as_str() {
+                "tags" => fm.tags.push(item),
+                "aliases" => fm.aliases.push(item),
+                _ => {}
+            }
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        current_key = key.trim().to_string();
+        match current_key.as_str() {
+            "title" if !value.trim().is_empty() => fm.title = Some(unquote_scalar(value)),
+            "tags" => fm.tags = parse_inline_list(value),
+            "aliases" | "alias" => {
+                current_key = "aliases".to_string();
+                fm.aliases = parse_inline_list(value);
+            }
+            key => {
+                fm.fields
+                    .insert(key.to_lowercase(), value.trim().to_string());
             }
         }
     }
+    fm
+}
+
+/// Match `text` against a glob pattern supporting `*`, `**` and `?`.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) if pattern.get(1) == Some(&b'*') => {
+            let rest = pattern[2..].strip_prefix(b"/").unwrap_or(&pattern[2..]);
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        (Some(b'*'), _) => {
+            let rest = &pattern[1..];
+            let max = text.iter().position(|&c| c == b'/').unwrap_or(text.len());
+            (0..=max).any(|i| glob_match(rest, &text[i..]))
+        }
+        (Some(b'?'), Some(&c)) if c != b'/' => glob_match(&pattern[1..], &text[1..]),
+        (Some(p), Some(c)) if p == c => glob_match(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+/// Load the ignore patterns from the vault's ignore file.
+pub fn load_ignore_patterns() -> Vec<String> {
+    fs::read_to_string(notes_dir().join(IGNORE_FILE))
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.trim_end_matches('/').to_string())
+        .collect()
+}
+
+/// Whether a vault-relative path is ignored. Hidden files and directories are
+/// always ignored; patterns without a `/` match any path component, others
+/// match the whole relative path.
+pub fn is_ignored(rel: &Path, patterns: &[String]) -> bool {
+    let rel_str = rel.to_string_lossy().replace('\\', "/");
+    let components: Vec<&str> = rel_str.split('/').filter(|c| !c.is_empty()).collect();
+    if components.iter().any(|c| c.starts_with('.')) {
+        return true;
+    }
+    patterns.iter().any(|pat| {
+        if pat.contains('/') {
+            let pat = pat.trim_start_matches('/');
+            glob_match(pat.as_bytes(), rel_str.as_bytes())
+        } else {
+            components
+                .iter()
+                .any(|c| glob_match(pat.as_bytes(), c.as_bytes()))
+        }
+    })
+}
+
+/// Walk the vault and return the paths of all non-ignored `.md` files.
+pub fn walk_notes() -> Vec<PathBuf> {
+    let root = notes_dir();
+    let patterns = load_ignore_patterns();
+    WalkDir::new(&root)
+        .into_iter()
+        .filter_entry(|e| {
+            let rel = e.path().strip_prefix(&root).unwrap_or(e.path());
+            rel.as_os_str().is_empty()
+                || (rel != Path::new(templates::TEMPLATES_DIR) && !is_ignored(rel, &patterns))
+        })
+        .filter_map(Result::ok)
+        .filter(|e| {
+            e.file_type().is_file() && e.path().extension().and_then(|s| s.to_str()) == Some("md")
+        })
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// A note as listed by `nrs list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteSummary {
+    pub slug: String,
+    pub title: String,
+    /// Path from the vault root.
+    pub path: String,
+    pub tags: Vec<String>,
+    /// Modification time in Unix seconds.
+    pub last_modified: u64,
+}
+
+/// Title, tags and modification time of every note in the vault.
+pub fn note_summaries() -> Vec<NoteSummary> {
+    let root = notes_dir();
+    walk_notes()
+        .into_iter()
+        .filter_map(|p| {
+            let slug = p.file_stem()?.to_string_lossy().to_string();
+            let content = fs::read_to_string(&p).ok()?;
+            let last_modified = fs::metadata(&p)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            Some(NoteSummary {
+                title: parse_front_matter(&content)
+                    .title
+                    .unwrap_or_else(|| slug.replace('_', " ")),
+                path: p
+                    .strip_prefix(&root)
+                    .unwrap_or(&p)
+                    .to_string_lossy()
+                    .to_string(),
+                tags: tags::extract_tags(&content),
+                slug,
+                last_modified,
+            })
+        })
+        .collect()
+}
+
+/// Load and return a sorted list of note stems.
+pub fn load_notes_list() -> Vec<String> {
+    let mut out: Vec<String> = walk_notes()
+        .iter()
+        .filter_map(|p| p.file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .collect();
     out.sort();
     out
 }
@@ -0,0 +1,255 @@
+use crate::config::{self, PeriodicSettings};
+use crate::notes::{self, notes_dir};
+use crate::templates;
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, Weekday};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How many periods to look back or ahead for the neighboring note.
+const MAX_GAP: u32 = 400;
+
+/// The kind of periodic note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Period {
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+            Period::Monthly => "monthly",
+        }
+    }
+
+    /// The settings of this period in the vault config.
+    fn settings(self) -> PeriodicSettings {
+        let config = config::load_config();
+        match self {
+            Period::Daily => config.daily,
+            Period::Weekly => config.weekly,
+            Period::Monthly => config.monthly,
+        }
+    }
+
+    fn default_format(self) -> &'static str {
+        match self {
+            Period::Daily => "%Y-%m-%d",
+            Period::Weekly => "%G-W%V",
+            Period::Monthly => "%Y-%m",
+        }
+    }
+
+    /// Heading of a new note without a template.
+    fn heading_format(self) -> &'static str {
+        match self {
+            Period::Daily => "%A, %B %-d, %Y",
+            Period::Weekly => "Week %V, %G",
+            Period::Monthly => "%B %Y",
+        }
+    }
+
+    /// The first day of the period containing `date`: the day itself, the
+    /// Monday of its ISO week, or the first of its month.
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Daily => date,
+            Period::Weekly => {
+                let week = date.iso_week();
+                NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Mon).unwrap_or(date)
+            }
+            Period::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// The start of the period `n` periods after (or before, if negative)
+    /// the one starting at `start`.
+    fn shift(self, start: NaiveDate, n: i64) -> Option<NaiveDate> {
+        match self {
+            Period::Daily => start.checked_add_signed(chrono::Duration::days(n)),
+            Period::Weekly => start.checked_add_signed(chrono::Duration::weeks(n)),
+            Period::Monthly if n >= 0 => start.checked_add_months(Months::new(n as u32)),
+            Period::Monthly => start.checked_sub_months(Months::new(n.unsigned_abs() as u32)),
+        }
+    }
+}
+
+/// Parse a `--date` value: `YYYY-MM-DD`, `today`, `yesterday` or `tomorrow`.
+pub fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    let today = Local::now().date_naive();
+    match s.trim().to_lowercase().as_str() {
+        "today" => Ok(today),
+        "yesterday" => Ok(today.pred_opt().unwrap_or(today)),
+        "tomorrow" => Ok(today.succ_opt().unwrap_or(today)),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", s)),
+    }
+}
+
+/// Path of the periodic note for the period containing `date`.
+pub fn periodic_path(period: Period, date: NaiveDate) -> PathBuf {
+    path_with(period, &period.settings(), date)
+}
+
+fn path_with(period: Period, settings: &PeriodicSettings, date: NaiveDate) -> PathBuf {
+    let folder = settings.folder.as_deref().unwrap_or(period.label());
+    let format = settings
+        .format
+        .as_deref()
+        .unwrap_or(period.default_format());
+    let name = period.start(date).format(format).to_string();
+    notes_dir().join(folder).join(format!("{}.md", name))
+}
+
+/// The nearest existing note before (`step` -1) or after (`step` 1) the
+/// period starting at `start`.
+fn neighbor(
+    period: Period,
+    settings: &PeriodicSettings,
+    start: NaiveDate,
+    step: i64,
+) -> Option<PathBuf> {
+    (1..=MAX_GAP as i64).find_map(|i| {
+        let path = path_with(period, settings, period.shift(start, i * step)?);
+        path.exists().then_some(path)
+    })
+}
+
+/// A quoted `"[[stem]]"` front matter value linking to the note at `path`.
+fn wikilink(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!("\"[[{}]]\"", stem)
+}
+
+/// Return the periodic note for the period containing `date`, creating it
+/// if needed. New notes get `previous` and `next` links to the nearest
+/// existing notes of the same period, and those notes are linked back.
+/// Returns the path and whether the note was created.
+pub fn open_periodic(period: Period, date: NaiveDate) -> io::Result<(PathBuf, bool)> {
+    let settings = period.settings();
+    let path = path_with(period, &settings, date);
+    if path.exists() {
+        return Ok((path, false));
+    }
+    let start = period.start(date);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut content = match &settings.template {
+        Some(name) => {
+            let mut fields = HashMap::new();
+            fields.insert("date".to_string(), start.format("%Y-%m-%d").to_string());
+            let filled =
+                templates::fill_template(&templates::load_template(name)?, &stem, &stem, &fields);
+            if notes::split_front_matter(&filled).0.is_some() {
+                filled
+            } else {
+                format!("---\ntitle: {}\ntags: []\n---\n{}", stem, filled)
+            }
+        }
+        None => format!(
+            "---\ntitle: {}\ndate: {}\ntags: [{}]\n---\n# {}\n\n",
+            stem,
+            start.format("%Y-%m-%d"),
+            period.label(),
+            start.format(period.heading_format())
+        ),
+    };
+
+    let previous = neighbor(period, &settings, start, -1);
+    let next = neighbor(period, &settings, start, 1);
+    if let Some(prev_path) = &previous {
+        content = notes::set_front_matter_field(&content, "previous", &wikilink(prev_path));
+    }
+    if let Some(next_path) = &next {
+        content = notes::set_front_matter_field(&content, "next", &wikilink(next_path));
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, content)?;
+
+    // Point the neighbors at the new note.
+    for (neighbor, key) in [(previous, "next"), (next, "previous")] {
+        if let Some(p) = neighbor {
+            let text = fs::read_to_string(&p)?;
+            fs::write(
+                &p,
+                notes::set_front_matter_field(&text, key, &wikilink(&path)),
+            )?;
+        }
+    }
+    Ok((path, true))
+}
+
+/// Notes belonging to a calendar day.
+#[derive(Debug, Clone, Default)]
+pub struct DayNotes {
+    /// Stem of the day's daily note.
+    pub daily: Option<String>,
+    /// Stems of other notes last modified that day.
+    pub modified: Vec<String>,
+}
+
+impl DayNotes {
+    /// The daily note first, then the modified notes.
+    pub fn stems(&self) -> Vec<String> {
+        self.daily.iter().chain(&self.modified).cloned().collect()
+    }
+}
+
+/// Daily notes and notes modified on each day of the month containing
+/// `date`. Days without either are left out.
+pub fn month_notes(date: NaiveDate) -> HashMap<NaiveDate, DayNotes> {
+    let first = Period::Monthly.start(date);
+    let settings = Period::Daily.settings();
+    let mut out: HashMap<NaiveDate, DayNotes> = HashMap::new();
+    let mut daily_paths = Vec::new();
+
+    let mut day = first;
+    while day.month() == first.month() {
+        let path = path_with(Period::Daily, &settings, day);
+        if path.exists() {
+            out.entry(day).or_default().daily =
+                path.file_stem().map(|s| s.to_string_lossy().to_string());
+            daily_paths.push(path);
+        }
+        match day.succ_opt() {
+            Some(next) => day = next,
+            None => break,
+        }
+    }
+
+    for path in notes::walk_notes() {
+        if daily_paths.contains(&path) {
+            continue;
+        }
+        let Some(modified) = fs::metadata(&path).and_then(|m| m.modified()).ok() else {
+            continue;
+        };
+        let day = DateTime::<Local>::from(modified).date_naive();
+        if day.year() == first.year() && day.month() == first.month() {
+            if let Some(stem) = path.file_stem() {
+                out.entry(day)
+                    .or_default()
+                    .modified
+                    .push(stem.to_string_lossy().to_string());
+            }
+        }
+    }
+    for notes in out.values_mut() {
+        notes.modified.sort();
+    }
+    out
+}
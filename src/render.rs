@@ -0,0 +1,337 @@
+use crate::links::{self, Anchor};
+use crate::notes;
+use crossterm::style::Stylize;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Embeds nested deeper than this are left as links.
+const MAX_EMBED_DEPTH: usize = 5;
+
+/// Escape text for HTML content and attributes.
+pub fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The URL a note link should point to, or `None` if the note is missing.
+/// Anchors become fragments matching the heading ids written by
+/// [`render_html`].
+fn link_href(
+    target: &str,
+    path: Option<&str>,
+    anchor: Option<&Anchor>,
+    from: &Path,
+) -> Option<String> {
+    let fragment = anchor
+        .map(|a| format!("#{}", a.fragment()))
+        .unwrap_or_default();
+    if target.is_empty() && path.is_none() {
+        return Some(fragment);
+    }
+    let resolved = links::resolve_target(target, path, from)?;
+    let stem = resolved.file_stem()?.to_string_lossy().to_string();
+    if resolved == from && !fragment.is_empty() {
+        return Some(fragment);
+    }
+    Some(format!("/notes/{}{}", stem, fragment))
+}
+
+/// The text shown for a wikilink: its label, or the target and anchor.
+fn wikilink_label(link: &links::WikiLink) -> String {
+    link.label.clone().unwrap_or_else(|| {
+        let mut text = link.target.clone();
+        if let Some(anchor) = &link.anchor {
+            text.push_str(&anchor.to_string());
+        }
+        text.trim_start_matches('#').to_string()
+    })
+}
+
+/// Replace `[[wikilinks]]` and trailing `^block-id` markers in a line of
+/// Markdown with inline HTML, leaving inline code alone.
+fn rewrite_line(line: &str, from: &Path, re_wiki: &Regex, re_block: &Regex) -> String {
+    let mut out = String::new();
+    for (i, segment) in line.split('`').enumerate() {
+        if i > 0 {
+            out.push('`');
+        }
+        // Odd segments are inside inline code.
+        if i % 2 == 1 {
+            out.push_str(segment);
+            continue;
+        }
+        let replaced = re_wiki.replace_all(segment, |caps: &Captures| {
+            let link = links::parse_wikilink(&caps[2]);
+            let label = wikilink_label(&link);
+            let target = crate::graph::normalize_id(&link.target);
+            match link_href(&target, None, link.anchor.as_ref(), from) {
+                Some(href) => format!(
+                    "<a class=\"wikilink\" href=\"{}\">{}</a>",
+                    html_escape(&href),
+                    html_escape(&label)
+                ),
+                None => format!(
+                    "<a class=\"wikilink broken\" href=\"/notes/{}\">{}</a>",
+                    html_escape(&target),
+                    html_escape(&label)
+                ),
+            }
+        });
+        out.push_str(&replaced);
+    }
+    re_block
+        .replace(&out, |caps: &Captures| {
+            format!(
+                "{}<span class=\"block-id\" id=\"^{}\"></span>",
+                &caps[1], &caps[2]
+            )
+        })
+        .to_string()
+}
+
+/// Notes (and the part of them) currently being embedded, outermost first.
+type EmbedStack = Vec<(PathBuf, Option<Anchor>)>;
+
+/// The content an `![[...]]` embed stands for, with its own embeds expanded,
+/// or `None` if the note or anchor is missing, the embed would loop back to
+/// a note already being embedded, or it nests too deep.
+fn embed(inner: &str, from: &Path, stack: &mut EmbedStack, html: bool) -> Option<String> {
+    let link = links::parse_wikilink(inner);
+    let target = crate::graph::normalize_id(&link.target);
+    let path = links::resolve_target(&target, None, from)?;
+    let key = (path, link.anchor);
+    if stack.len() > MAX_EMBED_DEPTH || stack.contains(&key) {
+        return None;
+    }
+    let content = fs::read_to_string(&key.0).ok()?;
+    let text = match &key.1 {
+        Some(anchor) => links::anchor_section(&content, anchor)?,
+        None => notes::split_front_matter(&content).1.trim().to_string(),
+    };
+    let stem = key.0.file_stem()?.to_string_lossy().to_string();
+    let path = key.0.clone();
+    stack.push(key);
+    let expanded = expand(&text, &path, stack, html);
+    stack.pop();
+    Some(if html {
+        format!(
+            "<div class=\"embed\" data-note=\"{}\">\n\n{}\n\n</div>",
+            html_escape(&stem),
+            expanded
+        )
+    } else {
+        expanded
+    })
+}
+
+/// Expand the embeds in a note body, outside code.
+fn expand(body: &str, from: &Path, stack: &mut EmbedStack, html: bool) -> String {
+    static RE_EMBED: OnceLock<Regex> = OnceLock::new();
+    let re_embed = RE_EMBED.get_or_init(|| Regex::new(r"!\[\[(.+?)\]\]").unwrap());
+    let mut in_fence = false;
+    let lines: Vec<String> = body
+        .split('\n')
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+            }
+            if in_fence || !line.contains("![[") {
+                return line.to_string();
+            }
+            let mut out = String::new();
+            for (i, segment) in line.split('`').enumerate() {
+                if i > 0 {
+                    out.push('`');
+                }
+                if i % 2 == 1 {
+                    out.push_str(segment);
+                    continue;
+                }
+                let replaced = re_embed.replace_all(segment, |caps: &Captures| {
+                    let Some(text) = embed(&caps[1], from, stack, html) else {
+                        return caps[0].to_string();
+                    };
+                    if !html && !text.contains('\n') {
+                        return text;
+                    }
+                    // Multi-line content starts and ends its own block.
+                    let whole = caps.get(0).unwrap();
+                    let before = segment[..whole.start()].trim().is_empty() && i == 0;
+                    let after = segment[whole.end()..].trim().is_empty();
+                    format!(
+                        "{}{}{}",
+                        if before { "" } else { "\n\n" },
+                        text,
+                        if after { "" } else { "\n\n" }
+                    )
+                });
+                out.push_str(&replaced);
+            }
+            out
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// A note's body with `![[note]]` and `![[note#heading]]` embeds replaced by
+/// the embedded content. Embeds that are missing, cyclic or nested too deep
+/// are left as written.
+pub fn expand_embeds(body: &str, from: &Path) -> String {
+    expand(body, from, &mut vec![(from.to_path_buf(), None)], false)
+}
+
+/// A note as a single Markdown document: its front matter followed by the
+/// body with all embeds expanded.
+pub fn flatten(content: &str, from: &Path) -> String {
+    let (_, body) = notes::split_front_matter(content);
+    let head = &content[..content.len() - body.len()];
+    let mut out = format!("{}{}", head, expand_embeds(body, from));
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// Render a note's Markdown body to HTML. Headings get ids matching
+/// [`links::headings`], `[[note#heading]]`, `[[note#^block]]` and
+/// `[text](note.md#heading)` links point to `/notes/<stem>#<id>`, block
+/// ids become anchors, and embeds are inlined in `<div class="embed">`.
+pub fn render_html(content: &str, from: &Path) -> String {
+    let re_wiki = Regex::new(r"(!?)\[\[(.+?)\]\]").unwrap();
+    let re_block = Regex::new(r"(^|\s)\^([A-Za-z0-9-]+)\s*$").unwrap();
+
+    let (_, body) = notes::split_front_matter(content);
+    let body = expand(body, from, &mut vec![(from.to_path_buf(), None)], true);
+    let mut in_fence = false;
+    let source: Vec<String> = body
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return line.to_string();
+            }
+            if in_fence {
+                line.to_string()
+            } else {
+                rewrite_line(line, from, &re_wiki, &re_block)
+            }
+        })
+        .collect();
+    let source = source.join("\n");
+
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut events: Vec<Event> = Parser::new_ext(&source, options).collect();
+
+    // Give headings the same ids as `links::headings`.
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for i in 0..events.len() {
+        if let Event::Start(Tag::Heading { .. }) = &events[i] {
+            let mut text = String::new();
+            let mut j = i + 1;
+            while j < events.len() && !matches!(events[j], Event::End(TagEnd::Heading(_))) {
+                if let Event::Text(t) | Event::Code(t) = &events[j] {
+                    text.push_str(t);
+                }
+                j += 1;
+            }
+            let base = links::heading_slug(&text);
+            let n = seen.entry(base.clone()).or_default();
+            let slug = if *n == 0 {
+                base
+            } else {
+                format!("{}-{}", base, n)
+            };
+            *n += 1;
+            if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
+                *id = Some(CowStr::from(slug));
+            }
+        }
+        // Point Markdown links to notes at the note page.
+        if let Event::Start(Tag::Link { dest_url, .. }) = &mut events[i] {
+            if let Some(md) = links::markdown_target(dest_url) {
+                if let Some(href) =
+                    link_href(&md.target, md.path.as_deref(), md.anchor.as_ref(), from)
+                {
+                    *dest_url = CowStr::from(href);
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+/// Style a line of Markdown for the terminal: links are underlined, strong
+/// text bold and inline code yellow. Block id markers are dropped.
+fn style_inline(line: &str, re_link: &Regex, re_strong: &Regex) -> String {
+    static RE_BLOCK: OnceLock<Regex> = OnceLock::new();
+    let re_block = RE_BLOCK.get_or_init(|| Regex::new(r"(^|\s)\^[A-Za-z0-9-]+\s*$").unwrap());
+    let line = re_block.replace(line, "");
+    let mut out = String::new();
+    for (i, segment) in line.split('`').enumerate() {
+        if i % 2 == 1 {
+            out.push_str(&segment.yellow().to_string());
+            continue;
+        }
+        let segment = re_strong.replace_all(segment, |caps: &Captures| caps[1].bold().to_string());
+        let segment = re_link.replace_all(&segment, |caps: &Captures| {
+            let text = match caps.get(1) {
+                Some(inner) => wikilink_label(&links::parse_wikilink(inner.as_str())),
+                None => caps[2].to_string(),
+            };
+            text.blue().underlined().to_string()
+        });
+        out.push_str(&segment);
+    }
+    out
+}
+
+/// Render a note for the terminal with ANSI styles: embeds are expanded,
+/// headings are bold, code is yellow, quotes are dimmed and links show
+/// their text.
+pub fn render_terminal(content: &str, from: &Path) -> String {
+    let re_link = Regex::new(r"!?\[\[(.+?)\]\]|\[([^\]]+)\]\([^)\s]+\)").unwrap();
+    let re_strong = Regex::new(r"\*\*([^*]+)\*\*").unwrap();
+    let re_heading = Regex::new(r"^(#{1,6})\s+(.*)$").unwrap();
+    let re_bullet = Regex::new(r"^(\s*)[-*+]\s").unwrap();
+
+    let (_, body) = notes::split_front_matter(content);
+    let body = expand_embeds(body, from);
+    let mut in_fence = false;
+    let lines: Vec<String> = body
+        .split('\n')
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return line.dark_grey().to_string();
+            }
+            if in_fence {
+                return line.yellow().to_string();
+            }
+            if let Some(caps) = re_heading.captures(line) {
+                let text = caps[2].trim_end_matches('#').trim_end();
+                let styled = text.bold().cyan();
+                return if caps[1].len() == 1 {
+                    styled.underlined().to_string()
+                } else {
+                    styled.to_string()
+                };
+            }
+            if line.trim_start().starts_with('>') {
+                return line.dark_grey().italic().to_string();
+            }
+            let line = re_bullet.replace(line, "${1}• ");
+            style_inline(&line, &re_link, &re_strong)
+        })
+        .collect();
+    lines.join("\n")
+}
@@ -0,0 +1,521 @@
+use crate::notes::{self, notes_dir};
+use crate::tags;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CAMEL: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// Which matcher the search overlay uses.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SearchMode {
+    /// Fuzzy match over titles, aliases and paths.
+    Fuzzy,
+    /// Case-insensitive substring match over note names and content.
+    Content,
+    /// Regular expression match over note names and content.
+    Regex,
+    /// Similarity ranking using offline embeddings.
+    Semantic,
+}
+
+impl SearchMode {
+    /// Cycle to the next search mode.
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::Fuzzy => SearchMode::Content,
+            SearchMode::Content => SearchMode::Regex,
+            SearchMode::Regex => SearchMode::Semantic,
+            SearchMode::Semantic => SearchMode::Fuzzy,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Fuzzy => "fuzzy",
+            SearchMode::Content => "content",
+            SearchMode::Regex => "regex",
+            SearchMode::Semantic => "semantic",
+        }
+    }
+}
+
+/// Result of a fuzzy match: the score and the matched character positions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    pub indices: Vec<usize>,
+}
+
+/// The searchable fields of a note.
+#[derive(Debug, Clone)]
+pub struct SearchEntry {
+    pub stem: String,
+    pub title: String,
+    pub aliases: Vec<String>,
+    pub path: String,
+}
+
+/// A note ranked by the fuzzy finder.
+#[derive(Debug, Clone)]
+pub struct RankedNote {
+    pub stem: String,
+    pub score: i64,
+    /// Matched character positions in the stem, empty if the best match
+    /// came from another field.
+    pub highlight: Vec<usize>,
+}
+
+/// Bonus for matching at position `j`, based on the preceding character.
+fn position_bonus(chars: &[char], j: usize) -> i64 {
+    if j == 0 {
+        return BONUS_BOUNDARY;
+    }
+    let (prev, cur) = (chars[j - 1], chars[j]);
+    if !prev.is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if (prev.is_lowercase() && cur.is_uppercase())
+        || (!prev.is_numeric() && cur.is_numeric())
+    {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+/// Fuzzy-match `pattern` against `candidate` using fzf-style scoring.
+///
+/// Matching is case-insensitive unless the pattern contains an uppercase
+/// character. Returns `None` if the pattern is not a subsequence of the
+/// candidate.
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let case_sensitive = pattern.chars().any(char::is_uppercase);
+    let fold = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+    let pat: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(fold)
+        .collect();
+    let orig: Vec<char> = candidate.chars().collect();
+    let cand: Vec<char> = orig.iter().copied().map(fold).collect();
+    let (n, m) = (pat.len(), cand.len());
+    if n == 0 {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: Vec::new(),
+        });
+    }
+
+    // Cheap subsequence check before running the full DP.
+    let mut it = cand.iter();
+    if !pat.iter().all(|p| it.any(|c| c == p)) {
+        return None;
+    }
+
+    let bonus: Vec<i64> = (0..m).map(|j| position_bonus(&orig, j)).collect();
+    let neg = i64::MIN / 2;
+    let mut score = vec![vec![neg; m]; n];
+    let mut from = vec![vec![usize::MAX; m]; n];
+
+    for i in 0..n {
+        // Best score of row i-1 ending at least two positions back, with gap
+        // penalties applied for the skipped characters.
+        let mut carry = neg;
+        let mut carry_from = usize::MAX;
+        for j in 0..m {
+            if i > 0 && j >= 2 {
+                carry += SCORE_GAP_EXTENSION;
+                let opened = score[i - 1][j - 2] + SCORE_GAP_START;
+                if opened > carry {
+                    carry = opened;
+                    carry_from = j - 2;
+                }
+            }
+            if cand[j] != pat[i] {
+                continue;
+            }
+            if i == 0 {
+                score[i][j] = SCORE_MATCH + bonus[j] * BONUS_FIRST_CHAR_MULTIPLIER;
+                continue;
+            }
+            let mut best = carry;
+            let mut best_from = carry_from;
+            if j >= 1 && score[i - 1][j - 1] > neg {
+                let consecutive = score[i - 1][j - 1] + BONUS_CONSECUTIVE;
+                if consecutive >= best {
+                    best = consecutive;
+                    best_from = j - 1;
+                }
+            }
+            if best > neg / 2 {
+                score[i][j] = best + SCORE_MATCH + bonus[j];
+                from[i][j] = best_from;
+            }
+        }
+    }
+
+    let (mut j, best) = score[n - 1]
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| **s > neg / 2)
+        .max_by_key(|(j, s)| (**s, std::cmp::Reverse(*j)))
+        .map(|(j, s)| (j, *s))?;
+
+    let mut indices = vec![0; n];
+    for i in (0..n).rev() {
+        indices[i] = j;
+        if i > 0 {
+            j = from[i][j];
+        }
+    }
+
+    Some(FuzzyMatch {
+        score: best,
+        indices,
+    })
+}
+
+/// Load the searchable fields (title, aliases, path) of every note.
+pub fn load_search_entries() -> Vec<SearchEntry> {
+    let root = notes_dir();
+    let mut out = Vec::new();
+    for p in notes::walk_notes() {
+        let p = p.as_path();
+        let Some(stem) = p.file_stem() else {
+            continue;
+        };
+        let stem = stem.to_string_lossy().to_string();
+        let fm = notes::parse_front_matter(&fs::read_to_string(p).unwrap_or_default());
+        out.push(SearchEntry {
+            title: fm.title.unwrap_or_else(|| stem.replace('_', " ")),
+            aliases: fm.aliases,
+            path: p
+                .strip_prefix(&root)
+                .unwrap_or(p)
+                .to_string_lossy()
+                .to_string(),
+            stem,
+        });
+    }
+    out.sort_by(|a, b| a.stem.cmp(&b.stem));
+    out
+}
+
+/// Rank entries against a fuzzy query, best match first.
+pub fn fuzzy_rank(entries: &[SearchEntry], query: &str) -> Vec<RankedNote> {
+    let mut ranked: Vec<RankedNote> = entries
+        .iter()
+        .filter_map(|e| {
+            let on_stem = fuzzy_match(query, &e.stem);
+            let best_other = std::iter::once(&e.title)
+                .chain(e.aliases.iter())
+                .chain(std::iter::once(&e.path))
+                .filter_map(|field| fuzzy_match(query, field))
+                .map(|m| m.score)
+                .max();
+            match (on_stem, best_other) {
+                (Some(m), other) if other.is_none_or(|o| m.score >= o) => Some(RankedNote {
+                    stem: e.stem.clone(),
+                    score: m.score,
+                    highlight: m.indices,
+                }),
+                (_, Some(score)) => Some(RankedNote {
+                    stem: e.stem.clone(),
+                    score,
+                    highlight: Vec::new(),
+                }),
+                _ => None,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.stem.cmp(&b.stem)));
+    ranked
+}
+
+/// Read the content of every note in `paths`, keyed by stem.
+pub fn note_contents(paths: &HashMap<String, PathBuf>) -> HashMap<String, String> {
+    paths
+        .iter()
+        .filter_map(|(stem, path)| Some((stem.clone(), fs::read_to_string(path).ok()?)))
+        .collect()
+}
+
+/// Return notes whose name or content contains `query` (case-insensitive).
+pub fn content_search(
+    notes: &[String],
+    contents: &HashMap<String, String>,
+    query: &str,
+) -> Vec<String> {
+    let query = query.to_lowercase();
+    notes
+        .iter()
+        .filter(|note| {
+            note.to_lowercase().contains(&query)
+                || contents
+                    .get(*note)
+                    .is_some_and(|c| c.to_lowercase().contains(&query))
+        })
+        .cloned()
+        .collect()
+}
+
+/// How `grep` treats letter case.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaseMode {
+    Sensitive,
+    Insensitive,
+    /// Insensitive unless the pattern contains an uppercase character.
+    Smart,
+}
+
+/// A single regex match in the vault.
+#[derive(Debug, Serialize)]
+pub struct GrepMatch {
+    /// Path relative to the vault root.
+    pub file: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// The matched text.
+    pub matched: String,
+    /// The full line containing the match.
+    pub text: String,
+}
+
+/// Compile a search regex honouring the case mode.
+pub fn build_regex(pattern: &str, case: CaseMode) -> Result<Regex, regex::Error> {
+    let insensitive = match case {
+        CaseMode::Sensitive => false,
+        CaseMode::Insensitive => true,
+        CaseMode::Smart => !pattern.chars().any(char::is_uppercase),
+    };
+    RegexBuilder::new(pattern)
+        .case_insensitive(insensitive)
+        .build()
+}
+
+/// Find every match of `re` across the vault, in path order.
+pub fn grep(re: &Regex) -> Vec<GrepMatch> {
+    let root = notes_dir();
+    let mut paths = notes::walk_notes();
+    paths.sort();
+
+    let mut out = Vec::new();
+    for p in paths {
+        let Ok(content) = fs::read_to_string(&p) else {
+            continue;
+        };
+        let file = p
+            .strip_prefix(&root)
+            .unwrap_or(&p)
+            .to_string_lossy()
+            .to_string();
+        for (line_idx, line) in content.lines().enumerate() {
+            for m in re.find_iter(line) {
+                out.push(GrepMatch {
+                    file: file.clone(),
+                    line: line_idx + 1,
+                    column: line[..m.start()].chars().count() + 1,
+                    matched: m.as_str().to_string(),
+                    text: line.to_string(),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Return notes whose name or content matches `re`.
+pub fn regex_search(
+    notes: &[String],
+    contents: &HashMap<String, String>,
+    re: &Regex,
+) -> Vec<String> {
+    notes
+        .iter()
+        .filter(|note| re.is_match(note) || contents.get(*note).is_some_and(|c| re.is_match(c)))
+        .cloned()
+        .collect()
+}
+
+/// A single condition in a search query.
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    /// `tag:name`
+    Tag(String),
+    /// `path:prefix`, matched against the vault-relative path
+    Path(String),
+    /// `title:text`
+    Title(String),
+    /// `modified:7d`, modified within the given duration
+    ModifiedWithin(Duration),
+    /// `key:value`, any other front matter field
+    Field(String, String),
+    /// Free text, matched against note name and content
+    Text(String),
+}
+
+/// A parsed search query. All terms must match; terms prefixed with `-` must
+/// not match.
+///
+/// Tags match nested tags too, so `tag:project` finds `project/alpha`.
+///
+/// Syntax: `tag:incident -tag:closed path:meetings/ modified:7d status:open "exact phrase"`.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    terms: Vec<(bool, Term)>,
+}
+
+/// Parse a duration like `12h`, `7d` or `2w`.
+pub fn parse_age(value: &str) -> Option<Duration> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let n: u64 = value[..split].parse().ok()?;
+    let unit = match &value[split..] {
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        _ => return None,
+    };
+    Some(Duration::from_secs(n * unit))
+}
+
+/// Split a query into whitespace-separated tokens, keeping quoted phrases.
+fn tokenize(input: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut cur = String::new();
+    let mut in_quotes = false;
+    for c in input.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !cur.is_empty() {
+                    out.push(std::mem::take(&mut cur));
+                }
+            }
+            c => cur.push(c),
+        }
+    }
+    if !cur.is_empty() {
+        out.push(cur);
+    }
+    out
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let terms = tokenize(input)
+            .into_iter()
+            .map(|tok| {
+                let (negated, tok) = match tok.strip_prefix('-') {
+                    Some(rest) if !rest.is_empty() => (true, rest.to_string()),
+                    _ => (false, tok),
+                };
+                let term = match tok.split_once(':') {
+                    Some((key, value))
+                        if !value.is_empty() && key.chars().all(|c| c.is_alphanumeric()) =>
+                    {
+                        let value = value.to_lowercase();
+                        match key.to_lowercase().as_str() {
+                            "tag" => Term::Tag(tags::normalize_tag(&value)),
+                            "path" => Term::Path(value),
+                            "title" => Term::Title(value),
+                            "modified" => match parse_age(&value) {
+                                Some(age) => Term::ModifiedWithin(age),
+                                None => Term::Text(tok.to_lowercase()),
+                            },
+                            key => Term::Field(key.to_string(), value),
+                        }
+                    }
+                    _ => Term::Text(tok.to_lowercase()),
+                };
+                (negated, term)
+            })
+            .collect();
+        Query { terms }
+    }
+
+    fn term_matches(term: &Term, note: &QueryNote) -> bool {
+        match term {
+            Term::Tag(t) => note.tags.iter().any(|nt| tags::tag_matches(t, nt)),
+            Term::Path(prefix) => note.path.to_lowercase().starts_with(prefix.as_str()),
+            Term::Title(t) => note
+                .fm
+                .title
+                .as_deref()
+                .unwrap_or(&note.stem)
+                .to_lowercase()
+                .contains(t.as_str()),
+            Term::ModifiedWithin(age) => note
+                .modified
+                .and_then(|m| m.elapsed().ok())
+                .is_some_and(|elapsed| elapsed <= *age),
+            Term::Field(key, value) => note
+                .fm
+                .fields
+                .get(key)
+                .is_some_and(|v| v.to_lowercase() == *value),
+            Term::Text(text) => {
+                note.stem.to_lowercase().contains(text.as_str())
+                    || note.content.to_lowercase().contains(text.as_str())
+            }
+        }
+    }
+
+    fn matches(&self, note: &QueryNote) -> bool {
+        self.terms
+            .iter()
+            .all(|(negated, term)| Self::term_matches(term, note) != *negated)
+    }
+}
+
+/// The data a query is evaluated against.
+struct QueryNote {
+    stem: String,
+    path: String,
+    fm: notes::FrontMatter,
+    tags: Vec<String>,
+    content: String,
+    modified: Option<SystemTime>,
+}
+
+/// Run a query over the vault and return the stems of matching notes, sorted.
+pub fn run_query(query: &Query) -> Vec<String> {
+    let root = notes_dir();
+    let mut out: Vec<String> = notes::walk_notes()
+        .into_iter()
+        .filter_map(|p| {
+            let content = fs::read_to_string(&p).ok()?;
+            let note = QueryNote {
+                stem: p.file_stem()?.to_string_lossy().to_string(),
+                path: p
+                    .strip_prefix(&root)
+                    .unwrap_or(&p)
+                    .to_string_lossy()
+                    .to_string(),
+                fm: notes::parse_front_matter(&content),
+                tags: tags::extract_tags(&content),
+                modified: fs::metadata(&p).and_then(|m| m.modified()).ok(),
+                content,
+            };
+            query.matches(&note).then_some(note.stem)
+        })
+        .collect();
+    out.sort();
+    out
+}
@@ -0,0 +1,207 @@
+use crate::config::vault_meta_dir;
+use crate::notes;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Turns text into a fixed-size vector for similarity search.
+pub trait Embedder {
+    /// Identifier stored with the index so a changed embedder invalidates it.
+    fn id(&self) -> String;
+    /// Embed `text` as an L2-normalized vector.
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Offline embedder using the hashing trick over words, word bigrams and
+/// character trigrams. No model files or network access required.
+pub struct HashedNgramEmbedder {
+    dims: usize,
+}
+
+impl Default for HashedNgramEmbedder {
+    fn default() -> Self {
+        Self { dims: 512 }
+    }
+}
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "has", "have", "in",
+    "is", "it", "its", "of", "on", "or", "that", "the", "this", "to", "was", "were", "will",
+    "with",
+];
+
+/// 64-bit FNV-1a, stable across builds so cached vectors stay valid.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl HashedNgramEmbedder {
+    fn add(&self, v: &mut [f32], feature: &str, weight: f32) {
+        let h = fnv1a(feature.as_bytes());
+        let idx = (h % self.dims as u64) as usize;
+        let sign = if h >> 63 == 0 { 1.0 } else { -1.0 };
+        v[idx] += sign * weight;
+    }
+}
+
+impl Embedder for HashedNgramEmbedder {
+    fn id(&self) -> String {
+        format!("hashed-ngram-{}", self.dims)
+    }
+
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut v = vec![0.0; self.dims];
+        let lower = text.to_lowercase();
+        let words: Vec<&str> = lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty() && !STOPWORDS.contains(w))
+            .collect();
+
+        for (i, word) in words.iter().enumerate() {
+            self.add(&mut v, &format!("w:{}", word), 1.0);
+            if let Some(next) = words.get(i + 1) {
+                self.add(&mut v, &format!("b:{} {}", word, next), 0.5);
+            }
+            let padded: Vec<char> = format!("<{}>", word).chars().collect();
+            for tri in padded.windows(3) {
+                self.add(
+                    &mut v,
+                    &format!("c:{}", tri.iter().collect::<String>()),
+                    0.25,
+                );
+            }
+        }
+
+        let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            v.iter_mut().for_each(|x| *x /= norm);
+        }
+        v
+    }
+}
+
+/// The embedder used when none is configured.
+pub fn default_embedder() -> Box<dyn Embedder + Send + Sync> {
+    Box::new(HashedNgramEmbedder::default())
+}
+
+/// Cosine similarity between two vectors.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let na = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let nb = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if na == 0.0 || nb == 0.0 {
+        0.0
+    } else {
+        dot / (na * nb)
+    }
+}
+
+/// Path of the cached vector index in the vault.
+pub fn index_path() -> PathBuf {
+    vault_meta_dir().join("cache").join("semantic-index.json")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    mtime: u64,
+    vector: Vec<f32>,
+}
+
+/// Note vectors keyed by path from the vault root, so notes with the same
+/// stem in different folders are kept apart. Refreshed incrementally by
+/// modification time.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VectorIndex {
+    embedder: String,
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+impl VectorIndex {
+    /// Load the cached index and re-embed notes that changed since it was
+    /// written. The cache is rewritten if anything changed.
+    pub fn load_or_build(embedder: &dyn Embedder) -> io::Result<Self> {
+        let mut index: VectorIndex = fs::read_to_string(index_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        if index.embedder != embedder.id() {
+            index = VectorIndex {
+                embedder: embedder.id(),
+                entries: BTreeMap::new(),
+            };
+        }
+
+        let mut changed = false;
+        let mut seen = HashSet::new();
+        for p in notes::walk_notes() {
+            let Some(stem) = p.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let key = notes::relative_path(&p);
+            let mtime = fs::metadata(&p)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            seen.insert(key.clone());
+            if index.entries.get(&key).is_some_and(|e| e.mtime == mtime) {
+                continue;
+            }
+            let content = fs::read_to_string(&p).unwrap_or_default();
+            let (_, body) = notes::split_front_matter(&content);
+            let title = notes::parse_front_matter(&content)
+                .title
+                .unwrap_or_else(|| stem.replace('_', " "));
+            let vector = embedder.embed(&format!("{}\n{}", title, body));
+            index.entries.insert(key, IndexEntry { mtime, vector });
+            changed = true;
+        }
+
+        let before = index.entries.len();
+        index.entries.retain(|key, _| seen.contains(key));
+        changed |= index.entries.len() != before;
+
+        if changed {
+            index.save()?;
+        }
+        Ok(index)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = index_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// The stored vector for a note, by its path from the vault root.
+    pub fn vector(&self, path: &str) -> Option<&[f32]> {
+        self.entries.get(path).map(|e| e.vector.as_slice())
+    }
+
+    /// Rank notes by cosine similarity to `query`, best first, as stems.
+    /// Notes with no similarity at all are left out.
+    pub fn search(&self, embedder: &dyn Embedder, query: &str, limit: usize) -> Vec<(String, f32)> {
+        let q = embedder.embed(query);
+        let mut hits: Vec<(String, f32)> = self
+            .entries
+            .iter()
+            .filter_map(|(key, e)| {
+                let stem = Path::new(key).file_stem()?.to_string_lossy().to_string();
+                Some((stem, cosine(&q, &e.vector)))
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hits.truncate(limit);
+        hits
+    }
+}
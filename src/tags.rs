@@ -0,0 +1,326 @@
+use crate::graph::NoteInfo;
+use crate::notes;
+use regex::Regex;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// A tag with the number of notes carrying it or one of its child tags.
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    /// Nesting depth: 0 for `project`, 1 for `project/alpha`.
+    pub depth: usize,
+    pub parent: Option<String>,
+    pub count: usize,
+    pub notes: Vec<String>,
+}
+
+/// Normalize a tag: strip a leading `#`, lowercase, replace spaces with
+/// underscores and drop empty path segments, so `#Project/ Alpha/` becomes
+/// `project/alpha`.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches('#')
+        .split('/')
+        .map(|seg| seg.trim().to_lowercase().replace(' ', "_"))
+        .filter(|seg| !seg.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The parent of a nested tag, `project/alpha` -> `project`.
+pub fn parent_tag(tag: &str) -> Option<&str> {
+    tag.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+/// A tag and all of its ancestors, outermost first.
+pub fn tag_ancestry(tag: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut cur = String::new();
+    for seg in tag.split('/') {
+        if !cur.is_empty() {
+            cur.push('/');
+        }
+        cur.push_str(seg);
+        out.push(cur.clone());
+    }
+    out
+}
+
+/// Whether `tag` is `query` or nested below it: `project` matches
+/// `project/alpha` but not `projects`.
+pub fn tag_matches(query: &str, tag: &str) -> bool {
+    tag == query
+        || tag
+            .strip_prefix(query)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Order tags so children directly follow their parent.
+fn tree_order(a: &str, b: &str) -> Ordering {
+    a.split('/').cmp(b.split('/'))
+}
+
+/// Whether an inline `#tag` is more likely a hex color such as `#fff` or
+/// `#1e90ff`.
+fn is_hex_color(tag: &str) -> bool {
+    matches!(tag.len(), 3 | 4 | 6 | 8) && tag.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Extract the normalized tags of a note: front matter `tags` plus inline
+/// `#tag` occurrences in the body, outside code. Inline tags start with a
+/// letter, `_` or `-`, and hex colors are skipped.
+pub fn extract_tags(content: &str) -> Vec<String> {
+    static RE_INLINE: OnceLock<Regex> = OnceLock::new();
+    static RE_CODE: OnceLock<Regex> = OnceLock::new();
+    let re_inline = RE_INLINE
+        .get_or_init(|| Regex::new(r"(?:^|[\s(\[,])#([\p{L}_\-][\p{L}\p{N}_\-/]*)").unwrap());
+    let re_code = RE_CODE.get_or_init(|| Regex::new(r"`[^`\n]*`").unwrap());
+
+    let mut tags: Vec<String> = notes::parse_front_matter(content)
+        .tags
+        .iter()
+        .map(|t| normalize_tag(t))
+        .collect();
+
+    let (_, body) = notes::split_front_matter(content);
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let line = re_code.replace_all(line, "");
+        for caps in re_inline.captures_iter(&line) {
+            if !is_hex_color(&caps[1]) {
+                tags.push(normalize_tag(&caps[1]));
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    tags.retain(|t| !t.is_empty() && seen.insert(t.clone()));
+    tags
+}
+
+/// Count notes per tag, including parent tags of nested tags, in tree order.
+pub fn tag_counts(notes: &[NoteInfo]) -> Vec<TagCount> {
+    let mut by_tag: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for info in notes {
+        for tag in &info.tags {
+            for ancestor in tag_ancestry(tag) {
+                let entry = by_tag.entry(ancestor).or_default();
+                if !entry.contains(&info.stem) {
+                    entry.push(info.stem.clone());
+                }
+            }
+        }
+    }
+    let mut out: Vec<TagCount> = by_tag
+        .into_iter()
+        .map(|(tag, mut notes)| {
+            notes.sort();
+            TagCount {
+                depth: tag.matches('/').count(),
+                parent: parent_tag(&tag).map(str::to_string),
+                count: notes.len(),
+                notes,
+                tag,
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| tree_order(&a.tag, &b.tag));
+    out
+}
+
+/// Whether a note carries every tag in `tags`, directly or via a child tag.
+pub fn has_all_tags(info: &NoteInfo, tags: &[String]) -> bool {
+    tags.iter()
+        .all(|q| info.tags.iter().any(|t| tag_matches(q, t)))
+}
+
+/// A bulk tag edit applied to note front matter.
+#[derive(Debug, Clone)]
+pub enum TagOp {
+    /// Rename a tag and its nested children in every note.
+    Rename { from: String, to: String },
+    /// Replace several tags (and their children) with one tag in every note.
+    Merge { sources: Vec<String>, into: String },
+    /// Add a tag to the given notes.
+    Add { tag: String, notes: Vec<String> },
+    /// Remove a tag from the given notes.
+    Remove { tag: String, notes: Vec<String> },
+}
+
+/// Replace the `from` prefix of a tag with `to`, keeping nested segments.
+fn retarget(tag: &str, from: &str, to: &str) -> Option<String> {
+    let norm = normalize_tag(tag);
+    if !tag_matches(from, &norm) {
+        return None;
+    }
+    Some(format!("{}{}", to, &norm[from.len()..]))
+}
+
+impl TagOp {
+    /// Normalize every tag in the operation, rejecting empty ones.
+    fn normalized(&self) -> io::Result<TagOp> {
+        let norm = |t: &String| {
+            let n = normalize_tag(t);
+            if n.is_empty() {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid tag: '{}'", t),
+                ))
+            } else {
+                Ok(n)
+            }
+        };
+        Ok(match self {
+            TagOp::Rename { from, to } => TagOp::Rename {
+                from: norm(from)?,
+                to: norm(to)?,
+            },
+            TagOp::Merge { sources, into } => TagOp::Merge {
+                sources: sources.iter().map(norm).collect::<io::Result<_>>()?,
+                into: norm(into)?,
+            },
+            TagOp::Add { tag, notes } => TagOp::Add {
+                tag: norm(tag)?,
+                notes: notes.clone(),
+            },
+            TagOp::Remove { tag, notes } => TagOp::Remove {
+                tag: norm(tag)?,
+                notes: notes.clone(),
+            },
+        })
+    }
+
+    /// Apply the operation to one note's tag list. Returns whether it changed.
+    fn apply(&self, stem: &str, tags: &mut Vec<String>) -> bool {
+        let before = tags.clone();
+        match self {
+            TagOp::Rename { from, to } => {
+                for t in tags.iter_mut() {
+                    if let Some(new) = retarget(t, from, to) {
+                        *t = new;
+                    }
+                }
+            }
+            TagOp::Merge { sources, into } => {
+                for t in tags.iter_mut() {
+                    if let Some(new) = sources.iter().find_map(|s| retarget(t, s, into)) {
+                        *t = new;
+                    }
+                }
+            }
+            TagOp::Add { tag, notes } => {
+                if notes.iter().any(|n| n == stem) && !tags.iter().any(|t| normalize_tag(t) == *tag)
+                {
+                    tags.push(tag.clone());
+                }
+            }
+            TagOp::Remove { tag, notes } => {
+                if notes.iter().any(|n| n == stem) {
+                    tags.retain(|t| normalize_tag(t) != *tag);
+                }
+            }
+        }
+        let mut seen = HashSet::new();
+        tags.retain(|t| seen.insert(normalize_tag(t)));
+        *tags != before
+    }
+
+    /// The notes this operation may touch, or `None` for the whole vault.
+    fn targets(&self) -> Option<&[String]> {
+        match self {
+            TagOp::Rename { .. } | TagOp::Merge { .. } => None,
+            TagOp::Add { notes, .. } | TagOp::Remove { notes, .. } => Some(notes),
+        }
+    }
+}
+
+/// Rewrite the `tags` entry of a note's front matter, leaving every other
+/// line untouched. A block list (`- tag` lines) stays a block list with the
+/// same indentation; otherwise tags are written inline. Adds a front matter
+/// block if the note has none.
+pub fn set_front_matter_tags(content: &str, tags: &[String]) -> String {
+    let entry = match block_list_indent(content) {
+        Some(indent) if !tags.is_empty() => {
+            let items: Vec<String> = tags
+                .iter()
+                .map(|t| format!("{}- {}", indent, notes::yaml_scalar(t)))
+                .collect();
+            format!("tags:\n{}", items.join("\n"))
+        }
+        _ => {
+            let items: Vec<String> = tags.iter().map(|t| notes::yaml_flow_scalar(t)).collect();
+            format!("tags: [{}]", items.join(", "))
+        }
+    };
+    notes::set_front_matter_entry(content, "tags", &entry)
+}
+
+/// The indentation of the items if the front matter `tags` entry is a
+/// block list, or `None` for an inline list or no entry.
+fn block_list_indent(content: &str) -> Option<String> {
+    let mut lines = notes::split_front_matter(content).0?.lines();
+    lines.find(|line| {
+        line.split_once(':')
+            .is_some_and(|(k, v)| k.trim_end() == "tags" && v.trim().is_empty())
+            && !line.starts_with(char::is_whitespace)
+    })?;
+    let item = lines.next()?;
+    let rest = item.trim_start();
+    rest.starts_with("- ")
+        .then(|| item[..item.len() - rest.len()].to_string())
+}
+
+/// Apply a bulk tag operation and return the stems of the notes that changed.
+/// Only front matter tags are rewritten; inline `#tags` in the body are left
+/// as written.
+pub fn apply_tag_op(op: &TagOp) -> io::Result<Vec<String>> {
+    let mut op = op.normalized()?;
+    let paths: Vec<(String, PathBuf)> = match op.targets() {
+        Some(targets) => {
+            let paths = targets
+                .iter()
+                .map(|target| match notes::resolve_note(target) {
+                    Some(p) => Ok((p.file_stem().unwrap().to_string_lossy().to_string(), p)),
+                    None => Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("Note not found: {}", target),
+                    )),
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            // Target the notes by their resolved stems.
+            if let TagOp::Add { notes, .. } | TagOp::Remove { notes, .. } = &mut op {
+                *notes = paths.iter().map(|(stem, _)| stem.clone()).collect();
+            }
+            paths
+        }
+        None => notes::walk_notes()
+            .into_iter()
+            .filter_map(|p| Some((p.file_stem()?.to_string_lossy().to_string(), p)))
+            .collect(),
+    };
+
+    let mut changed = Vec::new();
+    for (stem, path) in paths {
+        let content = fs::read_to_string(&path)?;
+        let mut tags = notes::parse_front_matter(&content).tags;
+        if op.apply(&stem, &mut tags) {
+            fs::write(&path, set_front_matter_tags(&content, &tags))?;
+            changed.push(stem);
+        }
+    }
+    changed.sort();
+    Ok(changed)
+}
@@ -0,0 +1,152 @@
+use crate::notes::{notes_dir, split_front_matter, yaml_scalar};
+use chrono::Local;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Vault folder holding note templates. It is not listed as notes.
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Template used by `nrs new` when none is given, if it exists.
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+/// Variables filled in for every template.
+const BUILTIN_VARS: [&str; 5] = ["title", "date", "time", "slug", "uuid"];
+
+/// A template with the custom fields it asks for.
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+/// Path to the templates folder, `~/notes/templates`.
+pub fn templates_dir() -> PathBuf {
+    notes_dir().join(TEMPLATES_DIR)
+}
+
+/// Names of the templates in the templates folder, sorted.
+pub fn list_templates() -> Vec<String> {
+    let mut out: Vec<String> = fs::read_dir(templates_dir())
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("md"))
+        .filter_map(|p| Some(p.file_stem()?.to_string_lossy().to_string()))
+        .collect();
+    out.sort();
+    out
+}
+
+/// Every template with its custom fields.
+pub fn template_infos() -> Vec<TemplateInfo> {
+    list_templates()
+        .into_iter()
+        .map(|name| TemplateInfo {
+            fields: load_template(&name)
+                .map(|t| template_fields(&t))
+                .unwrap_or_default(),
+            name,
+        })
+        .collect()
+}
+
+/// Read a template by name. Templates sit directly in the templates
+/// folder, so names with path separators or `..` are never found.
+pub fn load_template(name: &str) -> io::Result<String> {
+    let not_found = || {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Template not found: {}", name),
+        )
+    };
+    if name.contains(['/', '\\']) || name.contains("..") {
+        return Err(not_found());
+    }
+    let path = templates_dir().join(format!("{}.md", name.trim_end_matches(".md")));
+    fs::read_to_string(&path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => not_found(),
+        _ => e,
+    })
+}
+
+fn var_regex() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z0-9_-]+)\s*\}\}").unwrap()
+}
+
+/// Custom `{{field}}` names in a template, in order of first use. Built-in
+/// variables are not included.
+pub fn template_fields(template: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for caps in var_regex().captures_iter(template) {
+        let name = caps[1].to_lowercase();
+        if !BUILTIN_VARS.contains(&name.as_str()) && !out.contains(&name) {
+            out.push(name);
+        }
+    }
+    out
+}
+
+/// Fill in a template. `{{title}}`, `{{date}}` (`YYYY-MM-DD`), `{{time}}`
+/// (`HH:MM`), `{{slug}}` and `{{uuid}}` are built in; other variables come
+/// from `fields` and are left empty if missing. `fields` also overrides the
+/// built-in values, e.g. `date` for notes about another day. Front matter
+/// values that use variables are quoted if needed, so a title like
+/// `Re: budget` stays valid YAML.
+pub fn fill_template(
+    template: &str,
+    title: &str,
+    slug: &str,
+    fields: &HashMap<String, String>,
+) -> String {
+    let now = Local::now();
+    let uuid = uuid::Uuid::new_v4().to_string();
+    let fill = |text: &str| {
+        var_regex()
+            .replace_all(text, |caps: &Captures| {
+                let name = caps[1].to_lowercase();
+                if let Some(value) = fields.get(&name) {
+                    return value.clone();
+                }
+                match name.as_str() {
+                    "title" => title.to_string(),
+                    "date" => now.format("%Y-%m-%d").to_string(),
+                    "time" => now.format("%H:%M").to_string(),
+                    "slug" => slug.to_string(),
+                    "uuid" => uuid.clone(),
+                    _ => String::new(),
+                }
+            })
+            .to_string()
+    };
+    match split_front_matter(template) {
+        (Some(block), body) => {
+            let block: Vec<String> = block
+                .lines()
+                .map(|line| fill_front_matter_line(line, &fill))
+                .collect();
+            format!("---\n{}\n---\n{}", block.join("\n"), fill(body))
+        }
+        (None, _) => fill(template),
+    }
+}
+
+/// Fill a front matter line. A plain `key: value` that uses variables is
+/// written as a YAML scalar; quoted values and lists are filled as written.
+fn fill_front_matter_line(line: &str, fill: &dyn Fn(&str) -> String) -> String {
+    let filled = fill(line);
+    let Some((key, value)) = line.split_once(':') else {
+        return filled;
+    };
+    let value = value.trim();
+    let literal =
+        value.starts_with(['"', '\'', '[']) || (value.starts_with('{') && !value.starts_with("{{"));
+    if filled == line || value.is_empty() || literal {
+        return filled;
+    }
+    format!("{}: {}", key, yaml_scalar(&fill(value)))
+}
@@ -1,5 +1,16 @@
+use crate::ai::AiJobQueue;
 use crate::commands;
+use crate::config::{self, SavedSearch};
+use crate::graph::{self, NoteInfo, RelatedNote};
+use crate::links::{self, LinkRef};
 use crate::notes;
+use crate::periodic::{self, DayNotes, Period};
+use crate::render;
+use crate::search::{self, SearchEntry, SearchMode};
+use crate::semantic::{self, VectorIndex};
+use crate::tags::{self, TagCount};
+use crate::templates;
+use chrono::{Datelike, Local, Months, NaiveDate, Weekday};
 use crossterm::cursor;
 use crossterm::event::{
     self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
@@ -16,8 +27,10 @@ use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Ta
 use ratatui::Frame;
 use ratatui::Terminal;
 use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io;
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
 use std::time::Duration;
 
@@ -28,28 +41,101 @@ pub enum OverlayMode {
     NoteCreation,
     Help,
     Search,
+    /// Outgoing links of the selected note, to follow one.
+    Links,
+    /// Asks for the custom fields of the template a new note uses.
+    TemplateField,
+}
+
+/// A note waiting for its template's custom fields to be filled in.
+#[derive(Debug, Clone)]
+pub struct PendingNote {
+    pub title: String,
+    pub template: Option<String>,
+    /// Fields to ask for, in order; `values` holds the answered ones.
+    pub fields: Vec<String>,
+    pub values: HashMap<String, String>,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TabState {
     Notes,
     Preview,
+    Related,
+    Tags,
+    Calendar,
     AI,
 }
 
+/// Maximum number of notes listed for a semantic search.
+const SEMANTIC_RESULT_LIMIT: usize = 50;
+
+/// Start of the status message for a regex search pattern that fails to
+/// compile.
+const INVALID_REGEX: &str = "Invalid regex: ";
+
+/// Frames of the status bar spinner shown while an AI job runs.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
 /// TUI application state.
 pub struct AppState {
     pub notes: Vec<String>,
+    /// File of each note by stem, refreshed with the notes list.
+    pub note_paths: HashMap<String, PathBuf>,
     pub filtered_notes: Vec<String>,
     pub selected_idx: usize,
     pub overlay: OverlayMode,
     pub overlay_input: String,
     pub last_ai_output: Option<String>,
+    /// AI requests running in the background.
+    pub ai_jobs: AiJobQueue,
     pub preview: Option<String>,
     pub status_message: Option<String>,
     pub active_tab: TabState,
     pub search_query: Option<String>,
+    pub search_mode: SearchMode,
+    pub search_index: Vec<SearchEntry>,
+    /// Matched character positions per note, for highlighting fuzzy results.
+    pub search_highlights: HashMap<String, Vec<usize>>,
+    /// Vector index for semantic search, built on first use.
+    pub semantic_index: Option<VectorIndex>,
+    /// Saved searches, listed as virtual folders above the notes.
+    pub saved_searches: Vec<SavedSearch>,
+    pub active_saved_search: Option<String>,
+    /// Notes matched by the active saved search, run once when it is opened.
+    pub saved_search_notes: Vec<String>,
+    /// Content of every note by stem, read on the first content or regex search.
+    pub note_contents: Option<HashMap<String, String>>,
+    /// Links and tags of every note, scanned on first use.
+    pub graph_notes: Option<Vec<NoteInfo>>,
+    /// Notes related to the selected note, shown in the Related tab.
+    pub related: Vec<RelatedNote>,
+    /// All tags with note counts, shown in the Tags tab.
+    pub tag_counts: Vec<TagCount>,
+    pub tag_cursor: usize,
+    /// Tags the note list is filtered by; notes must carry all of them.
+    pub tag_filter: Vec<String>,
     pub show_tags: bool,
+    /// Lines scrolled past at the top of the preview.
+    pub preview_scroll: u16,
+    /// Front matter lines hidden above the preview, to map note lines to
+    /// preview lines.
+    pub preview_offset: usize,
+    /// Links listed by the links overlay and the one under the cursor.
+    pub note_links: Vec<LinkRef>,
+    pub link_cursor: usize,
+    /// Notes left by following links, most recent last.
+    pub link_history: Vec<String>,
+    /// Templates offered when creating a note, and the chosen one (0 for
+    /// none).
+    pub templates: Vec<String>,
+    pub template_choice: usize,
+    pub pending_note: Option<PendingNote>,
+    /// Day under the cursor in the Calendar tab, and the notes of its month.
+    pub calendar_date: NaiveDate,
+    pub calendar_notes: HashMap<NaiveDate, DayNotes>,
+    /// Day the note list is filtered to, with the notes of that day.
+    pub date_filter: Option<(NaiveDate, Vec<String>)>,
 }
 
 impl Default for AppState {
@@ -63,44 +149,78 @@ impl AppState {
         let notes_list = notes::load_notes_list();
         let mut s = Self {
             notes: notes_list.clone(),
+            note_paths: notes::note_paths(),
             filtered_notes: notes_list,
             selected_idx: 0,
             overlay: OverlayMode::None,
             overlay_input: String::new(),
             last_ai_output: None,
+            ai_jobs: AiJobQueue::default(),
             preview: None,
             status_message: None,
             active_tab: TabState::Notes,
             search_query: None,
+            search_mode: SearchMode::Fuzzy,
+            search_index: search::load_search_entries(),
+            search_highlights: HashMap::new(),
+            semantic_index: None,
+            saved_searches: Vec::new(),
+            active_saved_search: None,
+            saved_search_notes: Vec::new(),
+            note_contents: None,
+            graph_notes: None,
+            related: Vec::new(),
+            tag_counts: Vec::new(),
+            tag_cursor: 0,
+            tag_filter: Vec::new(),
             show_tags: false,
+            preview_scroll: 0,
+            preview_offset: 0,
+            note_links: Vec::new(),
+            link_cursor: 0,
+            link_history: Vec::new(),
+            templates: Vec::new(),
+            template_choice: 0,
+            pending_note: None,
+            calendar_date: Local::now().date_naive(),
+            calendar_notes: HashMap::new(),
+            date_filter: None,
         };
+        s.load_saved_searches();
+        s.selected_idx = s.folder_count();
         s.update_preview();
         s
     }
 
-    pub fn next_note(&mut self) {
-        let notes = if self.search_query.is_some() {
-            &self.filtered_notes
-        } else {
-            &self.notes
-        };
+    /// Load saved searches from the config. An invalid config keeps the
+    /// current ones and is reported in the status bar.
+    fn load_saved_searches(&mut self) {
+        match config::try_load_config() {
+            Ok(config) => self.saved_searches = config.saved_searches,
+            Err(e) => self.status_message = Some(e),
+        }
+    }
+
+    /// Number of saved-search folder rows shown above the notes.
+    pub fn folder_count(&self) -> usize {
+        self.saved_searches.len()
+    }
+
+    fn list_len(&self) -> usize {
+        self.folder_count() + self.filtered_notes.len()
+    }
 
-        if !notes.is_empty() {
-            self.selected_idx = (self.selected_idx + 1) % notes.len();
+    pub fn next_note(&mut self) {
+        if self.list_len() > 0 {
+            self.selected_idx = (self.selected_idx + 1) % self.list_len();
             self.update_preview();
         }
     }
 
     pub fn prev_note(&mut self) {
-        let notes = if self.search_query.is_some() {
-            &self.filtered_notes
-        } else {
-            &self.notes
-        };
-
-        if !notes.is_empty() {
+        if self.list_len() > 0 {
             if self.selected_idx == 0 {
-                self.selected_idx = notes.len() - 1;
+                self.selected_idx = self.list_len() - 1;
             } else {
                 self.selected_idx -= 1;
             }
@@ -108,19 +228,80 @@ impl AppState {
         }
     }
 
+    /// The currently selected note in the (possibly filtered) note list.
     pub fn selected_note(&self) -> Option<&String> {
-        if self.search_query.is_some() {
-            self.filtered_notes.get(self.selected_idx)
-        } else {
-            self.notes.get(self.selected_idx)
+        self.selected_idx
+            .checked_sub(self.folder_count())
+            .and_then(|i| self.filtered_notes.get(i))
+    }
+
+    /// The saved search under the cursor, if a folder row is selected.
+    pub fn selected_folder(&self) -> Option<&SavedSearch> {
+        self.saved_searches.get(self.selected_idx)
+    }
+
+    /// Whether the note list shows a subset of the notes.
+    pub fn is_filtered(&self) -> bool {
+        self.search_query.is_some()
+            || self.active_saved_search.is_some()
+            || !self.tag_filter.is_empty()
+            || self.date_filter.is_some()
+    }
+
+    /// The file of a note, or its path in the vault root if it isn't known.
+    pub fn note_file(&self, stem: &str) -> PathBuf {
+        self.note_paths
+            .get(stem)
+            .cloned()
+            .unwrap_or_else(|| notes::note_path(stem))
+    }
+
+    /// Open a saved search folder, or close the active one with `None`.
+    pub fn activate_saved_search(&mut self, name: Option<String>) {
+        self.active_saved_search = name;
+        self.search_query = None;
+        self.run_saved_search();
+        self.apply_search();
+    }
+
+    fn run_saved_search(&mut self) {
+        self.saved_search_notes = match self
+            .active_saved_search
+            .as_ref()
+            .and_then(|name| self.saved_searches.iter().find(|s| &s.name == name))
+        {
+            Some(saved) => search::run_query(&search::Query::parse(&saved.query)),
+            None => Vec::new(),
+        };
+    }
+
+    /// Reload notes, saved searches and the search index from disk.
+    pub fn reload_notes(&mut self) {
+        self.notes = notes::load_notes_list();
+        self.note_paths = notes::note_paths();
+        self.note_contents = None;
+        self.search_index = search::load_search_entries();
+        self.semantic_index = None;
+        self.graph_notes = None;
+        self.load_saved_searches();
+        self.update_tags();
+        self.update_calendar();
+        if let Some(name) = &self.active_saved_search {
+            if !self.saved_searches.iter().any(|s| &s.name == name) {
+                self.active_saved_search = None;
+            }
         }
+        self.run_saved_search();
+        self.apply_search();
     }
 
     pub fn update_preview(&mut self) {
         self.preview = None;
+        self.preview_scroll = 0;
+        self.preview_offset = 0;
         if let Some(st) = self.selected_note() {
-            let p = notes::note_path(st);
-            if let Ok(content) = fs::read_to_string(p) {
+            let p = self.note_file(st);
+            if let Ok(content) = fs::read_to_string(&p) {
                 // Extract and parse markdown content for better preview
                 let yaml_delim = Regex::new(r"^---\s*$").unwrap();
                 let mut lines: Vec<&str> = content.lines().collect();
@@ -133,50 +314,483 @@ impl AppState {
                         .position(|line| yaml_delim.is_match(line))
                     {
                         lines = lines[end_idx + 2..].to_vec();
+                        self.preview_offset = end_idx + 2;
                     }
                 }
 
-                // Take more lines for a richer preview
-                let preview_lines: Vec<_> = lines.into_iter().take(20).collect();
-                self.preview = Some(preview_lines.join("\n"));
+                // Inline `![[embeds]]` so composed notes read as a whole
+                self.preview = Some(render::expand_embeds(&lines.join("\n"), &p));
             }
         }
+        self.update_related();
+    }
+
+    /// Recount tags while the Tags tab is open or a tag filter is active.
+    pub fn update_tags(&mut self) {
+        if self.active_tab != TabState::Tags && self.tag_filter.is_empty() {
+            return;
+        }
+        let notes = self.graph_notes.get_or_insert_with(graph::scan_vault);
+        self.tag_counts = tags::tag_counts(notes);
+        self.tag_filter
+            .retain(|t| self.tag_counts.iter().any(|c| &c.tag == t));
+        self.tag_cursor = self.tag_cursor.min(self.tag_counts.len().saturating_sub(1));
+    }
+
+    pub fn next_tag(&mut self) {
+        if !self.tag_counts.is_empty() {
+            self.tag_cursor = (self.tag_cursor + 1) % self.tag_counts.len();
+        }
+    }
+
+    pub fn prev_tag(&mut self) {
+        if !self.tag_counts.is_empty() {
+            self.tag_cursor = self
+                .tag_cursor
+                .checked_sub(1)
+                .unwrap_or(self.tag_counts.len() - 1);
+        }
+    }
+
+    /// Add or remove the tag under the cursor from the tag filter.
+    pub fn toggle_tag_filter(&mut self) {
+        let Some(tag) = self.tag_counts.get(self.tag_cursor).map(|c| c.tag.clone()) else {
+            return;
+        };
+        if let Some(pos) = self.tag_filter.iter().position(|t| *t == tag) {
+            self.tag_filter.remove(pos);
+        } else {
+            self.tag_filter.push(tag);
+        }
+        self.apply_search();
+    }
+
+    pub fn clear_tag_filter(&mut self) {
+        self.tag_filter.clear();
+        self.apply_search();
+    }
+
+    /// Recompute related notes for the selection while the Related tab is open.
+    pub fn update_related(&mut self) {
+        self.related.clear();
+        if self.active_tab != TabState::Related {
+            return;
+        }
+        let Some(stem) = self.selected_note().cloned() else {
+            return;
+        };
+        if self.semantic_index.is_none() {
+            self.semantic_index =
+                VectorIndex::load_or_build(semantic::default_embedder().as_ref()).ok();
+        }
+        let notes = self.graph_notes.get_or_insert_with(graph::scan_vault);
+        self.related = graph::related_notes(notes, self.semantic_index.as_ref(), &stem, 20);
     }
 
     pub fn apply_search(&mut self) {
-        if let Some(query) = &self.search_query {
-            let query_lowercase = query.to_lowercase();
-            self.filtered_notes = self
-                .notes
+        self.search_highlights.clear();
+        // Text searches run within the active saved search, if any.
+        let mut base = if self.active_saved_search.is_some() {
+            self.saved_search_notes.clone()
+        } else {
+            self.notes.clone()
+        };
+        if !self.tag_filter.is_empty() {
+            let notes = self.graph_notes.get_or_insert_with(graph::scan_vault);
+            let tagged: HashSet<&str> = notes
                 .iter()
-                .filter(|note| {
-                    // Match note name
-                    let name_match = note.to_lowercase().contains(&query_lowercase);
-
-                    // Match note content
-                    let content_match = match fs::read_to_string(notes::note_path(note)) {
-                        Ok(content) => content.to_lowercase().contains(&query_lowercase),
-                        Err(_) => false,
-                    };
-
-                    name_match || content_match
-                })
-                .cloned()
+                .filter(|n| tags::has_all_tags(n, &self.tag_filter))
+                .map(|n| n.stem.as_str())
                 .collect();
-
-            self.selected_idx = 0;
-            self.update_preview();
+            base.retain(|stem| tagged.contains(stem.as_str()));
+        }
+        if let Some((_, stems)) = &self.date_filter {
+            let on_day: HashSet<&str> = stems.iter().map(String::as_str).collect();
+            base.retain(|stem| on_day.contains(stem.as_str()));
+        }
+        if let Some(query) = &self.search_query {
+            let in_base: HashSet<&str> = base.iter().map(String::as_str).collect();
+            self.filtered_notes = match self.search_mode {
+                SearchMode::Fuzzy => {
+                    let entries: Vec<SearchEntry> = self
+                        .search_index
+                        .iter()
+                        .filter(|e| in_base.contains(e.stem.as_str()))
+                        .cloned()
+                        .collect();
+                    search::fuzzy_rank(&entries, query)
+                        .into_iter()
+                        .map(|r| {
+                            if !r.highlight.is_empty() {
+                                self.search_highlights.insert(r.stem.clone(), r.highlight);
+                            }
+                            r.stem
+                        })
+                        .collect()
+                }
+                SearchMode::Content => {
+                    let contents = self
+                        .note_contents
+                        .get_or_insert_with(|| search::note_contents(&self.note_paths));
+                    search::content_search(&base, contents, query)
+                }
+                SearchMode::Regex => match search::build_regex(query, search::CaseMode::Smart) {
+                    Ok(re) => {
+                        // Clear a previous pattern error, but no other message.
+                        if self
+                            .status_message
+                            .as_deref()
+                            .is_some_and(|m| m.starts_with(INVALID_REGEX))
+                        {
+                            self.status_message = None;
+                        }
+                        let contents = self
+                            .note_contents
+                            .get_or_insert_with(|| search::note_contents(&self.note_paths));
+                        search::regex_search(&base, contents, &re)
+                    }
+                    Err(_) => {
+                        self.status_message = Some(format!("{}{}", INVALID_REGEX, query));
+                        Vec::new()
+                    }
+                },
+                SearchMode::Semantic => {
+                    let embedder = semantic::default_embedder();
+                    if self.semantic_index.is_none() {
+                        match VectorIndex::load_or_build(embedder.as_ref()) {
+                            Ok(index) => self.semantic_index = Some(index),
+                            Err(e) => {
+                                self.status_message = Some(format!("Semantic index error: {}", e))
+                            }
+                        }
+                    }
+                    match &self.semantic_index {
+                        Some(index) => index
+                            .search(embedder.as_ref(), query, SEMANTIC_RESULT_LIMIT)
+                            .into_iter()
+                            .map(|(stem, _)| stem)
+                            .filter(|stem| in_base.contains(stem.as_str()))
+                            .collect(),
+                        None => Vec::new(),
+                    }
+                }
+            };
         } else {
-            self.filtered_notes = self.notes.clone();
+            self.filtered_notes = base;
         }
+        self.selected_idx = self.folder_count();
+        self.update_preview();
+    }
+
+    /// Re-run the search with the text currently typed in the overlay.
+    pub fn update_incremental_search(&mut self) {
+        let query = self.overlay_input.trim();
+        self.search_query = if query.is_empty() {
+            None
+        } else {
+            Some(query.to_string())
+        };
+        self.apply_search();
     }
 
     pub fn toggle_tab(&mut self) {
         self.active_tab = match self.active_tab {
             TabState::Notes => TabState::Preview,
-            TabState::Preview => TabState::AI,
+            TabState::Preview => TabState::Related,
+            TabState::Related => TabState::Tags,
+            TabState::Tags => TabState::Calendar,
+            TabState::Calendar => TabState::AI,
             TabState::AI => TabState::Notes,
         };
+        self.update_related();
+        self.update_tags();
+        self.update_calendar();
+    }
+
+    /// Show the result of a finished AI job, if any.
+    pub fn poll_ai_jobs(&mut self) {
+        let Some(done) = self.ai_jobs.poll() else {
+            return;
+        };
+        match done.result {
+            Ok(text) => {
+                self.last_ai_output = Some(text);
+                self.status_message =
+                    Some(format!("AI {} done for '{}'", done.task.name(), done.stem));
+            }
+            Err(e) => {
+                self.last_ai_output = Some(format!("Error: {}", e));
+                self.status_message = Some(format!(
+                    "AI {} failed for '{}'",
+                    done.task.name(),
+                    done.stem
+                ));
+            }
+        }
+    }
+
+    /// Cancel the running AI job; queued jobs continue.
+    pub fn cancel_ai_job(&mut self) {
+        if let Some(job) = self.ai_jobs.cancel() {
+            self.status_message = Some(format!(
+                "Cancelled AI {} for '{}'",
+                job.task.name(),
+                job.stem
+            ));
+        }
+    }
+
+    /// Reload the month shown in the Calendar tab while it is open.
+    pub fn update_calendar(&mut self) {
+        if self.active_tab == TabState::Calendar {
+            self.calendar_notes = periodic::month_notes(self.calendar_date);
+        }
+    }
+
+    /// Move the calendar cursor by days, or by months with `months`.
+    pub fn move_calendar(&mut self, days: i64, months: i32) {
+        let old = self.calendar_date;
+        let moved = if months >= 0 {
+            old.checked_add_months(Months::new(months as u32))
+        } else {
+            old.checked_sub_months(Months::new(months.unsigned_abs()))
+        }
+        .and_then(|d| d.checked_add_signed(chrono::Duration::days(days)));
+        if let Some(date) = moved {
+            self.calendar_date = date;
+            if (date.year(), date.month()) != (old.year(), old.month()) {
+                self.update_calendar();
+            }
+        }
+    }
+
+    /// List the notes of the day under the calendar cursor; if they are
+    /// already listed, preview the selected one.
+    pub fn open_calendar_day(&mut self) {
+        let date = self.calendar_date;
+        if self.date_filter.as_ref().is_some_and(|(d, _)| *d == date) {
+            self.active_tab = TabState::Preview;
+            return;
+        }
+        let stems = self
+            .calendar_notes
+            .get(&date)
+            .map(DayNotes::stems)
+            .unwrap_or_default();
+        let Some(first) = stems.first().cloned() else {
+            self.status_message = Some(format!("No notes on {}", date));
+            return;
+        };
+        self.status_message = Some(format!("{} note(s) on {}", stems.len(), date));
+        self.date_filter = Some((date, stems));
+        self.apply_search();
+        self.select_note(&first);
+    }
+
+    pub fn clear_date_filter(&mut self) {
+        self.date_filter = None;
+        self.apply_search();
+    }
+
+    /// Select a note by stem, clearing filters that hide it.
+    pub fn select_note(&mut self, stem: &str) -> bool {
+        if !self.filtered_notes.iter().any(|n| n == stem) && self.is_filtered() {
+            self.search_query = None;
+            self.active_saved_search = None;
+            self.tag_filter.clear();
+            self.date_filter = None;
+            self.apply_search();
+        }
+        match self.filtered_notes.iter().position(|n| n == stem) {
+            Some(pos) => {
+                self.selected_idx = self.folder_count() + pos;
+                self.update_preview();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// List the selected note's links in the links overlay.
+    pub fn open_links(&mut self) {
+        let Some(stem) = self.selected_note() else {
+            return;
+        };
+        let content = fs::read_to_string(self.note_file(stem)).unwrap_or_default();
+        self.note_links = links::extract_links(&content);
+        self.link_cursor = 0;
+        if self.note_links.is_empty() {
+            self.status_message = Some("No links in this note".to_string());
+        } else {
+            self.overlay = OverlayMode::Links;
+        }
+    }
+
+    /// Follow the link under the cursor: open the target note in the
+    /// Preview tab, scrolled to the linked heading or block.
+    pub fn follow_link(&mut self) {
+        let (Some(link), Some(current)) = (
+            self.note_links.get(self.link_cursor).cloned(),
+            self.selected_note().cloned(),
+        ) else {
+            return;
+        };
+        self.overlay = OverlayMode::None;
+        let resolved = match links::resolve(&link, &self.note_file(&current)) {
+            Ok(r) => r,
+            Err(reason) => {
+                self.status_message = Some(format!("Broken link {}: {}", link.raw, reason));
+                return;
+            }
+        };
+        if resolved.stem != current {
+            if !self.select_note(&resolved.stem) {
+                self.status_message = Some(format!("Note not listed: {}", resolved.stem));
+                return;
+            }
+            self.link_history.push(current);
+        }
+        if let Some(line) = resolved.line {
+            // Embeds above the anchor push it down in the preview.
+            let content = fs::read_to_string(&resolved.path).unwrap_or_default();
+            let above: Vec<&str> = content
+                .lines()
+                .take(line.saturating_sub(1))
+                .skip(self.preview_offset)
+                .collect();
+            let row = if above.is_empty() {
+                0
+            } else {
+                render::expand_embeds(&above.join("\n"), &resolved.path)
+                    .split('\n')
+                    .count()
+            };
+            self.preview_scroll = row.min(u16::MAX as usize) as u16;
+        }
+        self.active_tab = TabState::Preview;
+        self.status_message = Some(format!("Opened {}", link.raw));
+    }
+
+    /// Open the note creation overlay with the vault's templates.
+    pub fn open_note_creation(&mut self) {
+        self.overlay = OverlayMode::NoteCreation;
+        self.overlay_input.clear();
+        self.templates = templates::list_templates();
+        self.template_choice = 0;
+    }
+
+    /// The template chosen in the note creation overlay.
+    pub fn selected_template(&self) -> Option<&String> {
+        self.template_choice
+            .checked_sub(1)
+            .and_then(|i| self.templates.get(i))
+    }
+
+    pub fn next_template(&mut self) {
+        self.template_choice = (self.template_choice + 1) % (self.templates.len() + 1);
+    }
+
+    /// Start creating a note with the typed title, asking for the
+    /// template's custom fields first if it has any.
+    pub fn begin_note(&mut self) {
+        let title = self.overlay_input.trim().to_string();
+        self.overlay_input.clear();
+        self.overlay = OverlayMode::None;
+        if title.is_empty() {
+            return;
+        }
+        let template = self.selected_template().cloned();
+        let text = match &template {
+            Some(name) => match templates::load_template(name) {
+                Ok(text) => text,
+                Err(e) => {
+                    self.status_message = Some(format!("Error: {}", e));
+                    return;
+                }
+            },
+            None => templates::load_template(templates::DEFAULT_TEMPLATE).unwrap_or_default(),
+        };
+        self.pending_note = Some(PendingNote {
+            title,
+            template,
+            fields: templates::template_fields(&text),
+            values: HashMap::new(),
+        });
+        self.next_template_field();
+    }
+
+    /// Store the typed value for the current template field.
+    pub fn submit_template_field(&mut self) {
+        let value = std::mem::take(&mut self.overlay_input);
+        if let Some(p) = &mut self.pending_note {
+            if let Some(field) = p.fields.get(p.values.len()).cloned() {
+                p.values.insert(field, value);
+            }
+        }
+        self.next_template_field();
+    }
+
+    /// Ask for the next unanswered field, or create the note once all are
+    /// answered.
+    fn next_template_field(&mut self) {
+        let Some(p) = &self.pending_note else {
+            return;
+        };
+        if p.values.len() < p.fields.len() {
+            self.overlay = OverlayMode::TemplateField;
+            return;
+        }
+        let p = self.pending_note.take().unwrap();
+        self.overlay = OverlayMode::None;
+        match notes::create_note(&p.title, p.template.as_deref(), &p.values, None, None) {
+            Ok((path, created)) => {
+                self.reload_notes();
+                if let Some(stem) = path.file_stem() {
+                    self.select_note(&stem.to_string_lossy());
+                }
+                self.status_message = Some(if created {
+                    format!("Note created: {}", p.title)
+                } else {
+                    format!("Opened existing note: {}", p.title)
+                });
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Error: {}", e));
+            }
+        }
+    }
+
+    /// Select today's daily note, creating it if needed, and preview it.
+    pub fn open_today(&mut self) {
+        match periodic::open_periodic(Period::Daily, Local::now().date_naive()) {
+            Ok((path, created)) => {
+                if created {
+                    self.reload_notes();
+                }
+                let stem = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if self.select_note(&stem) {
+                    self.active_tab = TabState::Preview;
+                }
+                self.status_message = Some(format!(
+                    "{} daily note: {}",
+                    if created { "Created" } else { "Opened" },
+                    stem
+                ));
+            }
+            Err(e) => self.status_message = Some(format!("Error: {}", e)),
+        }
+    }
+
+    /// Return to the note a link was followed from.
+    pub fn link_back(&mut self) {
+        if let Some(stem) = self.link_history.pop() {
+            if self.select_note(&stem) {
+                self.status_message = Some(format!("Back to {}", stem));
+            }
+        }
     }
 }
 
@@ -191,10 +805,12 @@ pub fn run_tui() -> io::Result<()> {
     let res = (|| {
         let mut st = AppState::new();
         loop {
+            st.poll_ai_jobs();
             terminal.draw(|f| ui(f, &st))?;
 
             if event::poll(Duration::from_millis(50))? {
                 let ev = event::read()?;
+                #[allow(clippy::single_match)]
                 match ev {
                     Event::Key(KeyEvent {
                         code, modifiers, ..
@@ -206,13 +822,74 @@ pub fn run_tui() -> io::Result<()> {
                             break;
                         }
                         match st.overlay {
+                            OverlayMode::None
+                                if code == KeyCode::Esc && st.ai_jobs.running().is_some() =>
+                            {
+                                st.cancel_ai_job()
+                            }
+                            OverlayMode::None
+                                if st.active_tab == TabState::Tags
+                                    && matches!(
+                                        code,
+                                        KeyCode::Char('j')
+                                            | KeyCode::Char('k')
+                                            | KeyCode::Down
+                                            | KeyCode::Up
+                                            | KeyCode::Enter
+                                            | KeyCode::Char(' ')
+                                            | KeyCode::Esc
+                                    ) =>
+                            {
+                                match code {
+                                    KeyCode::Char('j') | KeyCode::Down => st.next_tag(),
+                                    KeyCode::Char('k') | KeyCode::Up => st.prev_tag(),
+                                    KeyCode::Esc => st.clear_tag_filter(),
+                                    _ => st.toggle_tag_filter(),
+                                }
+                            }
+                            OverlayMode::None
+                                if st.active_tab == TabState::Calendar
+                                    && matches!(
+                                        code,
+                                        KeyCode::Left
+                                            | KeyCode::Right
+                                            | KeyCode::Up
+                                            | KeyCode::Down
+                                            | KeyCode::Char('<')
+                                            | KeyCode::Char('>')
+                                            | KeyCode::Enter
+                                            | KeyCode::Esc
+                                    ) =>
+                            {
+                                match code {
+                                    KeyCode::Left => st.move_calendar(-1, 0),
+                                    KeyCode::Right => st.move_calendar(1, 0),
+                                    KeyCode::Up => st.move_calendar(-7, 0),
+                                    KeyCode::Down => st.move_calendar(7, 0),
+                                    KeyCode::Char('<') => st.move_calendar(0, -1),
+                                    KeyCode::Char('>') => st.move_calendar(0, 1),
+                                    KeyCode::Esc => st.clear_date_filter(),
+                                    _ => st.open_calendar_day(),
+                                }
+                            }
                             OverlayMode::None => match code {
                                 KeyCode::Char('j') | KeyCode::Down => st.next_note(),
                                 KeyCode::Char('k') | KeyCode::Up => st.prev_note(),
-                                KeyCode::Char('n') => {
-                                    st.overlay = OverlayMode::NoteCreation;
-                                    st.overlay_input.clear();
+                                KeyCode::Enter => {
+                                    if let Some(name) = st.selected_folder().map(|s| s.name.clone())
+                                    {
+                                        if st.active_saved_search.as_ref() == Some(&name) {
+                                            st.activate_saved_search(None);
+                                            st.status_message =
+                                                Some("Showing all notes".to_string());
+                                        } else {
+                                            st.status_message =
+                                                Some(format!("Saved search: {}", name));
+                                            st.activate_saved_search(Some(name));
+                                        }
+                                    }
                                 }
+                                KeyCode::Char('n') => st.open_note_creation(),
                                 KeyCode::Char('e') => {
                                     if let Some(sn) = st.selected_note() {
                                         let ed_result = open_in_editor(sn);
@@ -220,6 +897,7 @@ pub fn run_tui() -> io::Result<()> {
                                             Ok(_exit_status) => {
                                                 st.status_message =
                                                     Some(format!("Edited note: {}", sn));
+                                                st.note_contents = None;
                                                 execute!(io::stdout(), Clear(ClearType::All))?;
                                                 st.update_preview();
                                             }
@@ -239,9 +917,17 @@ pub fn run_tui() -> io::Result<()> {
                                 }
                                 KeyCode::Char('/') => {
                                     st.overlay = OverlayMode::Search;
-                                    st.overlay_input.clear();
+                                    st.overlay_input = st.search_query.clone().unwrap_or_default();
                                 }
                                 KeyCode::Tab => st.toggle_tab(),
+                                KeyCode::Char('T') => {
+                                    st.active_tab = TabState::Tags;
+                                    st.update_tags();
+                                }
+                                KeyCode::Char('C') => {
+                                    st.active_tab = TabState::Calendar;
+                                    st.update_calendar();
+                                }
                                 KeyCode::Char('t') => {
                                     st.show_tags = !st.show_tags;
                                     st.status_message = Some(format!(
@@ -249,14 +935,17 @@ pub fn run_tui() -> io::Result<()> {
                                         if st.show_tags { "on" } else { "off" }
                                     ));
                                 }
+                                KeyCode::Char('l') => st.open_links(),
+                                KeyCode::Char('D') => st.open_today(),
+                                KeyCode::Backspace => st.link_back(),
+                                KeyCode::PageDown => {
+                                    st.preview_scroll = st.preview_scroll.saturating_add(10)
+                                }
+                                KeyCode::PageUp => {
+                                    st.preview_scroll = st.preview_scroll.saturating_sub(10)
+                                }
                                 KeyCode::Char('r') => {
-                                    st.notes = notes::load_notes_list();
-                                    if let Some(_query) = &st.search_query {
-                                        st.apply_search();
-                                    } else {
-                                        st.filtered_notes = st.notes.clone();
-                                    }
-                                    st.update_preview();
+                                    st.reload_notes();
                                     st.status_message = Some("Notes refreshed".to_string());
                                 }
                                 _ => {}
@@ -276,7 +965,7 @@ pub fn run_tui() -> io::Result<()> {
                                 }
                                 KeyCode::Char('\t') => {
                                     let partial = st.overlay_input.trim_start_matches(':');
-                                    let matches: Vec<_> = ["summarize", "keywords"]
+                                    let matches: Vec<_> = ["summarize", "keywords", "saved", "tag"]
                                         .iter()
                                         .filter(|x| x.starts_with(partial))
                                         .collect();
@@ -297,26 +986,23 @@ pub fn run_tui() -> io::Result<()> {
                                 KeyCode::Backspace => {
                                     st.overlay_input.pop();
                                 }
-                                KeyCode::Enter => {
-                                    let title = st.overlay_input.trim().to_string();
-                                    if !title.is_empty() {
-                                        match notes::create_new_note(&title) {
-                                            Ok(_) => {
-                                                st.notes = notes::load_notes_list();
-                                                st.filtered_notes = st.notes.clone();
-                                                st.selected_idx = 0;
-                                                st.update_preview();
-                                                st.status_message =
-                                                    Some(format!("Note created: {}", title));
-                                            }
-                                            Err(e) => {
-                                                st.status_message = Some(format!("Error: {}", e));
-                                            }
-                                        }
-                                    }
+                                KeyCode::Tab => st.next_template(),
+                                KeyCode::Enter => st.begin_note(),
+                                KeyCode::Char(c) => {
+                                    st.overlay_input.push(c);
+                                }
+                                _ => {}
+                            },
+                            OverlayMode::TemplateField => match code {
+                                KeyCode::Esc => {
                                     st.overlay = OverlayMode::None;
                                     st.overlay_input.clear();
+                                    st.pending_note = None;
                                 }
+                                KeyCode::Backspace => {
+                                    st.overlay_input.pop();
+                                }
+                                KeyCode::Enter => st.submit_template_field(),
                                 KeyCode::Char(c) => {
                                     st.overlay_input.push(c);
                                 }
@@ -326,26 +1012,25 @@ pub fn run_tui() -> io::Result<()> {
                                 KeyCode::Esc => {
                                     st.overlay = OverlayMode::None;
                                     st.overlay_input.clear();
+                                    st.update_incremental_search();
                                 }
                                 KeyCode::Enter => {
-                                    let query = st.overlay_input.trim().to_string();
-                                    if query.is_empty() {
-                                        st.search_query = None;
-                                        st.filtered_notes = st.notes.clone();
-                                    } else {
-                                        st.search_query = Some(query);
-                                        st.apply_search();
-                                    }
-                                    st.selected_idx = 0;
                                     st.overlay = OverlayMode::None;
                                     st.overlay_input.clear();
-                                    st.update_preview();
                                 }
+                                KeyCode::Tab => {
+                                    st.search_mode = st.search_mode.next();
+                                    st.update_incremental_search();
+                                }
+                                KeyCode::Down => st.next_note(),
+                                KeyCode::Up => st.prev_note(),
                                 KeyCode::Backspace => {
                                     st.overlay_input.pop();
+                                    st.update_incremental_search();
                                 }
                                 KeyCode::Char(c) => {
                                     st.overlay_input.push(c);
+                                    st.update_incremental_search();
                                 }
                                 _ => {}
                             },
@@ -355,6 +1040,22 @@ pub fn run_tui() -> io::Result<()> {
                                 }
                                 _ => {}
                             },
+                            OverlayMode::Links => match code {
+                                KeyCode::Esc | KeyCode::Char('l') => {
+                                    st.overlay = OverlayMode::None;
+                                }
+                                KeyCode::Char('j') | KeyCode::Down => {
+                                    st.link_cursor = (st.link_cursor + 1) % st.note_links.len();
+                                }
+                                KeyCode::Char('k') | KeyCode::Up => {
+                                    st.link_cursor = st
+                                        .link_cursor
+                                        .checked_sub(1)
+                                        .unwrap_or(st.note_links.len() - 1);
+                                }
+                                KeyCode::Enter => st.follow_link(),
+                                _ => {}
+                            },
                         }
                     }
                     _ => {}
@@ -370,7 +1071,15 @@ pub fn run_tui() -> io::Result<()> {
     res
 }
 
-/// Open the note in an external editor.
+/// Run `$EDITOR` (or `$VISUAL`, or `vi`) on a file and wait for it to exit.
+pub fn spawn_editor(path: &Path) -> io::Result<ExitStatus> {
+    let ed = std::env::var("EDITOR")
+        .or_else(|_| std::env::var("VISUAL"))
+        .unwrap_or_else(|_| "vi".to_string());
+    Command::new(ed).arg(path).spawn()?.wait()
+}
+
+/// Open the note in an external editor, suspending the TUI meanwhile.
 pub fn open_in_editor(stem: &str) -> io::Result<ExitStatus> {
     // Save the terminal state
     let mut stdout = io::stdout();
@@ -380,13 +1089,7 @@ pub fn open_in_editor(stem: &str) -> io::Result<ExitStatus> {
     // Clear the screen to avoid leftovers
     execute!(stdout, Clear(ClearType::All))?;
 
-    // Launch the editor
-    let ed = std::env::var("EDITOR")
-        .or_else(|_| std::env::var("VISUAL"))
-        .unwrap_or_else(|_| "vi".to_string());
-    let p = notes::note_path(stem);
-    let mut child = Command::new(ed).arg(p).spawn()?;
-    let exit_status = child.wait()?;
+    let exit_status = spawn_editor(&notes::find_note_path(stem))?;
 
     // Properly restore the terminal state
     execute!(stdout, Clear(ClearType::All))?;
@@ -415,13 +1118,19 @@ pub fn ui<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState) {
     let tab_titles = vec![
         Spans::from("Notes"),
         Spans::from("Preview"),
+        Spans::from("Related"),
+        Spans::from("Tags"),
+        Spans::from("Calendar"),
         Spans::from("AI"),
     ];
     let tabs = Tabs::new(tab_titles)
         .select(match st.active_tab {
             TabState::Notes => 0,
             TabState::Preview => 1,
-            TabState::AI => 2,
+            TabState::Related => 2,
+            TabState::Tags => 3,
+            TabState::Calendar => 4,
+            TabState::AI => 5,
         })
         .style(Style::default())
         .highlight_style(
@@ -439,37 +1148,78 @@ pub fn ui<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState) {
         .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
         .split(chunks[1]);
 
-    // Render note list (always visible)
-    let notes_to_display = if st.search_query.is_some() {
-        &st.filtered_notes
-    } else {
-        &st.notes
-    };
-
-    let items: Vec<ListItem> = notes_to_display
+    // Render saved searches as virtual folders above the notes
+    let selected_style = Style::default()
+        .bg(Color::Blue)
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    let mut items: Vec<ListItem> = st
+        .saved_searches
         .iter()
         .enumerate()
-        .map(|(i, text)| {
-            let display_text = text.replace('_', " ");
+        .map(|(i, saved)| {
+            let active = st.active_saved_search.as_ref() == Some(&saved.name);
             let style = if i == st.selected_idx {
+                selected_style
+            } else if active {
                 Style::default()
-                    .bg(Color::Blue)
-                    .fg(Color::White)
+                    .fg(Color::Magenta)
                     .add_modifier(Modifier::BOLD)
             } else {
-                Style::default()
+                Style::default().fg(Color::Magenta)
             };
-
-            // Show search status if applicable
-
-            ListItem::new(display_text).style(style)
+            let icon = if active { "▾" } else { "▸" };
+            ListItem::new(format!("{} {}", icon, saved.name)).style(style)
         })
         .collect();
 
-    let list_title = if let Some(query) = &st.search_query {
-        format!("Notes (filtered: {})", query)
+    // Render note list (always visible)
+    items.extend(st.filtered_notes.iter().enumerate().map(|(i, text)| {
+        let display_text = text.replace('_', " ");
+        let style = if i + st.folder_count() == st.selected_idx {
+            selected_style
+        } else {
+            Style::default()
+        };
+
+        // Highlight fuzzy-matched characters
+        match st.search_highlights.get(text) {
+            Some(indices) => {
+                let spans: Vec<Span> = display_text
+                    .chars()
+                    .enumerate()
+                    .map(|(ci, c)| {
+                        if indices.contains(&ci) {
+                            Span::styled(
+                                c.to_string(),
+                                Style::default()
+                                    .fg(Color::Yellow)
+                                    .add_modifier(Modifier::BOLD),
+                            )
+                        } else {
+                            Span::raw(c.to_string())
+                        }
+                    })
+                    .collect();
+                ListItem::new(Spans::from(spans)).style(style)
+            }
+            None => ListItem::new(display_text).style(style),
+        }
+    }));
+
+    let list_title = match (&st.search_query, &st.active_saved_search) {
+        (Some(query), _) => format!("Notes ({}: {})", st.search_mode.label(), query),
+        (None, Some(saved)) => format!("Notes [{}]", saved),
+        (None, None) => "Notes".to_string(),
+    };
+    let list_title = if st.tag_filter.is_empty() {
+        list_title
     } else {
-        "Notes".to_string()
+        format!("{} #{}", list_title, st.tag_filter.join(" #"))
+    };
+    let list_title = match &st.date_filter {
+        Some((date, _)) => format!("{} @{}", list_title, date),
+        None => list_title,
     };
 
     let left = List::new(items).block(
@@ -485,38 +1235,15 @@ pub fn ui<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState) {
         TabState::Notes => {
             // If in Notes tab, show a more detailed view of the selected note
             if let Some(note) = st.selected_note() {
-                let note_path = notes::note_path(note);
+                let note_path = st.note_file(note);
                 if let Ok(content) = fs::read_to_string(note_path) {
                     // Create a nicer display with YAML front matter parsed
-                    let yaml_delim = Regex::new(r"^---\s*$").unwrap();
-                    let mut lines: Vec<&str> = content.lines().collect();
-
-                    let mut title = note.replace('_', " ");
-                    let mut tags = Vec::new();
-
-                    // Extract title and tags from YAML if present
-                    if lines.len() > 2 && yaml_delim.is_match(lines[0]) {
-                        if let Some(end_idx) = lines
-                            .iter()
-                            .skip(1)
-                            .position(|line| yaml_delim.is_match(line))
-                        {
-                            let yaml_section = &lines[1..end_idx + 1];
-                            for line in yaml_section {
-                                if line.starts_with("title:") {
-                                    title = line.trim_start_matches("title:").trim().to_string();
-                                }
-                                if line.starts_with("tags:") {
-                                    let tags_str = line.trim_start_matches("tags:").trim();
-                                    let tags_str =
-                                        tags_str.trim_start_matches('[').trim_end_matches(']');
-                                    tags =
-                                        tags_str.split(',').map(|s| s.trim().to_string()).collect();
-                                }
-                            }
-                            lines = lines[end_idx + 2..].to_vec();
-                        }
-                    }
+                    let title = notes::parse_front_matter(&content)
+                        .title
+                        .unwrap_or_else(|| note.replace('_', " "));
+                    let tags = tags::extract_tags(&content);
+                    let (_, body) = notes::split_front_matter(&content);
+                    let lines = body.lines();
 
                     // Create spans with title and tags highlighted
                     let mut text_spans = Vec::new();
@@ -585,13 +1312,106 @@ pub fn ui<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState) {
                         .border_type(BorderType::Rounded)
                         .title("Preview"),
                 )
-                .wrap(Wrap { trim: false });
+                .wrap(Wrap { trim: false })
+                .scroll((st.preview_scroll, 0));
 
             f.render_widget(preview, main_split[1]);
         }
+        TabState::Related => {
+            // Show related notes with the reasons behind each score
+            let lines: Vec<Spans> = if st.related.is_empty() {
+                vec![Spans::from("No related notes found.")]
+            } else {
+                st.related
+                    .iter()
+                    .flat_map(|r| {
+                        let mut head = vec![
+                            Span::styled(
+                                format!("{:.2}  ", r.score),
+                                Style::default().fg(Color::Yellow),
+                            ),
+                            Span::styled(
+                                r.slug.replace('_', " "),
+                                Style::default()
+                                    .fg(Color::White)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                        ];
+                        if !r.shared_tags.is_empty() {
+                            head.push(Span::styled(
+                                format!("  #{}", r.shared_tags.join(" #")),
+                                Style::default().fg(Color::Cyan),
+                            ));
+                        }
+                        let reasons = Span::styled(
+                            format!(
+                                "      tags {:.2} · links {:.2} · text {:.2}",
+                                r.reasons.tags, r.reasons.links, r.reasons.text
+                            ),
+                            Style::default().fg(Color::DarkGray),
+                        );
+                        vec![Spans::from(head), Spans::from(reasons)]
+                    })
+                    .collect()
+            };
+
+            let related = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .title("Related Notes"),
+                )
+                .wrap(Wrap { trim: false });
+
+            f.render_widget(related, main_split[1]);
+        }
+        TabState::Tags => {
+            // Show all tags with counts; selected tags filter the note list
+            let items: Vec<ListItem> = st
+                .tag_counts
+                .iter()
+                .enumerate()
+                .map(|(i, tc)| {
+                    let checked = st.tag_filter.contains(&tc.tag);
+                    let style = if i == st.tag_cursor {
+                        Style::default()
+                            .bg(Color::Blue)
+                            .fg(Color::White)
+                            .add_modifier(Modifier::BOLD)
+                    } else if checked {
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    };
+                    let name = tc.tag.rsplit('/').next().unwrap_or(&tc.tag);
+                    ListItem::new(format!(
+                        "[{}] {}#{} ({})",
+                        if checked { "x" } else { " " },
+                        "  ".repeat(tc.depth),
+                        name,
+                        tc.count
+                    ))
+                    .style(style)
+                })
+                .collect();
+
+            let tag_list = List::new(items).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title("Tags (j/k: move, Enter/Space: filter, Esc: clear)"),
+            );
+            f.render_widget(tag_list, main_split[1]);
+        }
+        TabState::Calendar => draw_calendar(f, st, main_split[1]),
         TabState::AI => {
             // Show AI output or guidance
-            let ai_content = if let Some(ai) = &st.last_ai_output {
+            let ai_content = if let Some((_, _, partial)) = st.ai_jobs.running() {
+                partial.to_string()
+            } else if let Some(ai) = &st.last_ai_output {
                 ai.clone()
             } else {
                 "No AI output. Use :summarize or :keywords command.".to_string()
@@ -646,7 +1466,7 @@ pub fn ui<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState) {
     let mut status_parts = Vec::new();
 
     // Add note count info
-    let note_count_text = if st.search_query.is_some() {
+    let note_count_text = if st.is_filtered() {
         format!("{}/{} notes", st.filtered_notes.len(), st.notes.len())
     } else {
         format!("{} notes", st.notes.len())
@@ -655,6 +1475,26 @@ pub fn ui<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState) {
     status_parts.push(Span::raw(note_count_text));
     status_parts.push(Span::raw(" | "));
 
+    if let Some((job, elapsed, _)) = st.ai_jobs.running() {
+        let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+        let waiting = match st.ai_jobs.queued() {
+            0 => String::new(),
+            n => format!(", {} queued", n),
+        };
+        status_parts.push(Span::styled(
+            format!(
+                "{} AI {} '{}' {}s{} (Esc: cancel)",
+                frame,
+                job.task.name(),
+                job.stem,
+                elapsed.as_secs(),
+                waiting
+            ),
+            Style::default().fg(Color::Yellow),
+        ));
+        status_parts.push(Span::raw(" | "));
+    }
+
     // Add status message if any
     if let Some(msg) = &st.status_message {
         status_parts.push(Span::styled(msg, Style::default().fg(Color::Green)));
@@ -663,14 +1503,7 @@ pub fn ui<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState) {
     }
 
     let status_txt = Spans::from(status_parts);
-    let status_par = Paragraph::new(status_txt)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Plain)
-                .title(""),
-        )
-        .alignment(Alignment::Left);
+    let status_par = Paragraph::new(status_txt).alignment(Alignment::Left);
     f.render_widget(status_par, chunks[3]);
 
     // Draw overlays last so they appear on top
@@ -680,13 +1513,123 @@ pub fn ui<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState) {
             draw_overlay(f, "[Command]", &st.overlay_input, main_split[1])
         }
         OverlayMode::NoteCreation => {
-            draw_overlay(f, "[New Note Title]", &st.overlay_input, main_split[1])
+            let title = if st.templates.is_empty() {
+                "[New Note Title]".to_string()
+            } else {
+                format!(
+                    "[New Note Title] template: {} (Tab: change)",
+                    st.selected_template().map_or("none", String::as_str)
+                )
+            };
+            draw_overlay(f, &title, &st.overlay_input, main_split[1])
         }
-        OverlayMode::Search => draw_overlay(f, "[Search]", &st.overlay_input, main_split[1]),
-        OverlayMode::Help => draw_help_overlay(f, main_split[1]),
+        OverlayMode::TemplateField => {
+            let title = st
+                .pending_note
+                .as_ref()
+                .and_then(|p| p.fields.get(p.values.len()))
+                .map(|field| format!("[{}]", field))
+                .unwrap_or_default();
+            draw_overlay(f, &title, &st.overlay_input, main_split[1])
+        }
+        OverlayMode::Search => draw_overlay(
+            f,
+            &format!("[Search: {}] (Tab: mode)", st.search_mode.label()),
+            &st.overlay_input,
+            main_split[1],
+        ),
+        OverlayMode::Help => draw_help_overlay(f, f.size()),
+        OverlayMode::Links => draw_links_overlay(f, st, main_split[1]),
     }
 }
 
+/// Draw the month around the calendar cursor. Days with a daily note are
+/// green, days with other modified notes yellow; the notes of the day under
+/// the cursor are listed below the grid.
+pub fn draw_calendar<B: ratatui::backend::Backend>(f: &mut Frame<B>, st: &AppState, area: Rect) {
+    let cursor = st.calendar_date;
+    let first = cursor.with_day(1).unwrap_or(cursor);
+    let today = Local::now().date_naive();
+
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            format!("{:^28}", first.format("%B %Y").to_string()),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(Span::styled(
+            " Mo  Tu  We  Th  Fr  Sa  Su",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let mut week: Vec<Span> = vec![Span::raw(
+        "    ".repeat(first.weekday().num_days_from_monday() as usize),
+    )];
+    let mut day = first;
+    while day.month() == first.month() {
+        let notes = st.calendar_notes.get(&day);
+        let mut style = match notes {
+            Some(n) if n.daily.is_some() => Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            Some(_) => Style::default().fg(Color::Yellow),
+            None => Style::default(),
+        };
+        if day == today {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        if day == cursor {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        week.push(Span::styled(format!(" {:>2} ", day.day()), style));
+        if day.weekday() == Weekday::Sun {
+            lines.push(Spans::from(std::mem::take(&mut week)));
+        }
+        match day.succ_opt() {
+            Some(next) => day = next,
+            None => break,
+        }
+    }
+    if !week.is_empty() {
+        lines.push(Spans::from(week));
+    }
+
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        cursor.format("%A, %B %-d, %Y").to_string(),
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    match st.calendar_notes.get(&cursor) {
+        Some(notes) => {
+            if let Some(daily) = &notes.daily {
+                lines.push(Spans::from(vec![
+                    Span::styled("  daily   ", Style::default().fg(Color::Green)),
+                    Span::raw(daily.clone()),
+                ]));
+            }
+            for stem in &notes.modified {
+                lines.push(Spans::from(vec![
+                    Span::styled("  edited  ", Style::default().fg(Color::Yellow)),
+                    Span::raw(stem.clone()),
+                ]));
+            }
+        }
+        None => lines.push(Spans::from(Span::styled(
+            "  No notes",
+            Style::default().fg(Color::DarkGray),
+        ))),
+    }
+
+    let calendar = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title("Calendar (←/→/↑/↓: day, </>: month, Enter: list/open, Esc: clear)"),
+    );
+    f.render_widget(calendar, area);
+}
+
 /// Draw a generic overlay.
 pub fn draw_overlay<B: ratatui::backend::Backend>(
     f: &mut Frame<B>,
@@ -712,23 +1655,51 @@ pub fn draw_overlay<B: ratatui::backend::Backend>(
     f.render_widget(par, overlay_rect);
 }
 
-/// Draw the help overlay.
-pub fn draw_help_overlay<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: Rect) {
-    // Center the help modal in the content area
-    let w = area.width.saturating_sub(10).min(80); // Max width of 80
-    let h = area.height.saturating_sub(6).min(20); // Max height of 20
-
-    // Center the help box in the area
-    let x = area.x + (area.width.saturating_sub(w)) / 2;
-    let y = area.y + (area.height.saturating_sub(h)) / 2;
-
+/// Draw the list of the selected note's links.
+pub fn draw_links_overlay<B: ratatui::backend::Backend>(
+    f: &mut Frame<B>,
+    st: &AppState,
+    area: Rect,
+) {
+    let w = area.width.saturating_sub(6);
+    let h = (st.note_links.len() as u16 + 2).min(area.height.saturating_sub(4));
     let overlay_rect = Rect {
-        x,
-        y,
+        x: area.x + 3,
+        y: area.y + 2,
         width: w,
         height: h,
     };
+    let items: Vec<ListItem> = st
+        .note_links
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            let style = if i == st.link_cursor {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!("{:>4} ", l.line),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(l.raw.clone(), style),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title("[Links] (Enter: follow, Esc: close)"),
+    );
+    f.render_widget(ratatui::widgets::Clear, overlay_rect);
+    f.render_widget(list, overlay_rect);
+}
 
+/// Draw the help overlay.
+pub fn draw_help_overlay<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: Rect) {
     // Create visually enhanced help content
     let lines = vec![
         Spans::from(vec![Span::styled(
@@ -754,7 +1725,16 @@ pub fn draw_help_overlay<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: R
         Spans::from(vec![
             Span::raw("  "),
             Span::styled("Tab", Style::default().fg(Color::Green)),
-            Span::raw(": Switch between Notes/Preview/AI tabs"),
+            Span::raw(": Switch between Notes/Preview/Related/Tags/Calendar/AI tabs"),
+        ]),
+        Spans::from(vec![
+            Span::raw("  "),
+            Span::styled("l", Style::default().fg(Color::Green)),
+            Span::raw(": Follow a link ("),
+            Span::styled("Backspace", Style::default().fg(Color::Green)),
+            Span::raw(" goes back, "),
+            Span::styled("PgUp/PgDn", Style::default().fg(Color::Green)),
+            Span::raw(" scroll the preview)"),
         ]),
         Spans::from(""),
         Spans::from(vec![Span::styled(
@@ -766,13 +1746,20 @@ pub fn draw_help_overlay<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: R
         Spans::from(vec![
             Span::raw("  "),
             Span::styled("n", Style::default().fg(Color::Green)),
-            Span::raw(": Create new note"),
+            Span::raw(": Create new note ("),
+            Span::styled("Tab", Style::default().fg(Color::Green)),
+            Span::raw(" picks a template)"),
         ]),
         Spans::from(vec![
             Span::raw("  "),
             Span::styled("e", Style::default().fg(Color::Green)),
             Span::raw(": Edit current note in $EDITOR"),
         ]),
+        Spans::from(vec![
+            Span::raw("  "),
+            Span::styled("D", Style::default().fg(Color::Green)),
+            Span::raw(": Open today's daily note"),
+        ]),
         Spans::from(vec![
             Span::raw("  "),
             Span::styled("r", Style::default().fg(Color::Green)),
@@ -788,7 +1775,9 @@ pub fn draw_help_overlay<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: R
         Spans::from(vec![
             Span::raw("  "),
             Span::styled("/", Style::default().fg(Color::Green)),
-            Span::raw(": Search notes (by title and content)"),
+            Span::raw(": Search notes as you type ("),
+            Span::styled("Tab", Style::default().fg(Color::Green)),
+            Span::raw(" cycles fuzzy/content/regex/semantic)"),
         ]),
         Spans::from(vec![
             Span::raw("  "),
@@ -797,13 +1786,38 @@ pub fn draw_help_overlay<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: R
             Span::styled(":summarize", Style::default().fg(Color::Magenta)),
             Span::raw(", "),
             Span::styled(":keywords", Style::default().fg(Color::Magenta)),
+            Span::raw(", "),
+            Span::styled(":saved <name>", Style::default().fg(Color::Magenta)),
             Span::raw(")"),
         ]),
+        Spans::from(vec![
+            Span::raw("    "),
+            Span::styled(
+                ":tag rename|merge|add|remove",
+                Style::default().fg(Color::Magenta),
+            ),
+            Span::raw(": Edit tags in front matter"),
+        ]),
+        Spans::from(vec![
+            Span::raw("  "),
+            Span::styled("Esc", Style::default().fg(Color::Green)),
+            Span::raw(": Cancel the running AI request"),
+        ]),
         Spans::from(vec![
             Span::raw("  "),
             Span::styled("t", Style::default().fg(Color::Green)),
             Span::raw(": Toggle tag display"),
         ]),
+        Spans::from(vec![
+            Span::raw("  "),
+            Span::styled("T", Style::default().fg(Color::Green)),
+            Span::raw(": Browse tags and filter notes by tag"),
+        ]),
+        Spans::from(vec![
+            Span::raw("  "),
+            Span::styled("C", Style::default().fg(Color::Green)),
+            Span::raw(": Calendar of daily and modified notes"),
+        ]),
         Spans::from(""),
         Spans::from(vec![Span::styled(
             "General",
@@ -827,6 +1841,23 @@ pub fn draw_help_overlay<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: R
         ]),
     ];
 
+    // Center the help modal, tall enough for every line once wrapped
+    let w = area.width.saturating_sub(10).min(80); // Max width of 80
+    let inner = w.saturating_sub(2).max(1) as usize;
+    let rows: usize = lines.iter().map(|l| l.width().max(1).div_ceil(inner)).sum();
+    let h = (rows as u16 + 2).min(area.height);
+
+    // Center the help box in the area
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+
+    let overlay_rect = Rect {
+        x,
+        y,
+        width: w,
+        height: h,
+    };
+
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
@@ -843,6 +1874,6 @@ pub fn draw_help_overlay<B: ratatui::backend::Backend>(f: &mut Frame<B>, area: R
         .alignment(Alignment::Left)
         .wrap(Wrap { trim: false });
 
-    // Create a slightly transparent background
+    f.render_widget(ratatui::widgets::Clear, overlay_rect);
     f.render_widget(par, overlay_rect);
 }
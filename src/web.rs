@@ -1,26 +1,28 @@
-use crate::notes::{note_path, notes_dir};
+use crate::ai::{self, AiTask};
+use crate::analytics;
+use crate::config::{load_config, ConflictPolicy};
+use crate::export::{ExportGraph, GraphFormat};
+use crate::graph;
+use crate::links;
+use crate::notes::{self, load_notes_list, parse_front_matter, walk_notes, NoteError};
+use crate::periodic::{self, Period};
+use crate::render;
+use crate::search::{self, run_query, Query};
+use crate::semantic::{self, VectorIndex};
+use crate::tags;
+use crate::templates;
 use actix_files::{Files, NamedFile};
 use actix_web::{middleware, web, App, HttpResponse, HttpServer, Responder};
+use futures::channel::mpsc;
+use futures::StreamExt;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::Path;
-use walkdir::WalkDir;
-
-/// A note node in the graph.
-#[derive(Debug, Serialize)]
-pub struct NoteNode {
-    pub id: String,
-    pub is_tag: bool,
-}
-
-/// A note link in the graph.
-#[derive(Debug, Serialize)]
-pub struct NoteLink {
-    pub source: String,
-    pub target: String,
-}
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Note data for API
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,8 +49,33 @@ pub async fn serve_notes(port: u16) -> io::Result<()> {
             .wrap(middleware::Logger::new("%a %r %s %b %T"))
             // Serve API Routes first - high priority
             .route("/api/notes", web::get().to(notes_list_api))
+            .route("/api/notes", web::post().to(create_note_api))
+            .route("/api/templates", web::get().to(templates_api))
+            .route("/api/daily/{date}", web::get().to(daily_api))
+            .route("/api/weekly/{date}", web::get().to(weekly_api))
+            .route("/api/monthly/{date}", web::get().to(monthly_api))
             .route("/api/notes/{stem}", web::get().to(note_detail_api))
+            .route(
+                "/api/notes/{stem}/related",
+                web::get().to(related_notes_api),
+            )
+            .route("/api/notes/{stem}/backlinks", web::get().to(backlinks_api))
+            .route("/api/ai/{task}/{stem}", web::post().to(ai_task_api))
+            .route("/api/ai/{task}/{stem}/stream", web::get().to(ai_stream_api))
             .route("/api/graph-data", web::get().to(graph_data))
+            .route("/api/graph/analytics", web::get().to(graph_analytics_api))
+            .route("/api/graph/path", web::get().to(graph_path_api))
+            .route("/api/search", web::get().to(search_api))
+            .route("/api/tags", web::get().to(tags_api))
+            .route("/api/tags/rename", web::post().to(rename_tag_api))
+            .route("/api/tags/merge", web::post().to(merge_tags_api))
+            .route("/api/tags/add", web::post().to(add_tag_api))
+            .route("/api/tags/remove", web::post().to(remove_tag_api))
+            .route("/api/saved-searches", web::get().to(saved_searches_api))
+            .route(
+                "/api/saved-searches/{name}/results",
+                web::get().to(saved_search_results_api),
+            )
             // Serve specific app.js and app.css files with proper MIME types
             .route("/assets/{filename:.*}", web::get().to(serve_assets))
             // Serve source files for development builds
@@ -76,70 +103,454 @@ pub async fn notes_list_api() -> impl Responder {
     HttpResponse::Ok().json(notes)
 }
 
+/// Body of `POST /api/notes`.
+#[derive(Debug, Deserialize)]
+pub struct CreateNoteBody {
+    pub title: String,
+    /// Template name from the vault's templates folder.
+    pub template: Option<String>,
+    /// Values for the template's custom fields.
+    #[serde(default)]
+    pub fields: HashMap<String, String>,
+    /// `error`, `suffix` or `open` when the file name is taken; defaults to
+    /// the vault's `slug.on_conflict` setting.
+    pub on_conflict: Option<ConflictPolicy>,
+}
+
+/// Create a note, optionally from a template.
+pub async fn create_note_api(body: web::Json<CreateNoteBody>) -> impl Responder {
+    let body = body.into_inner();
+    let text = match &body.template {
+        Some(name) => match templates::load_template(name) {
+            Ok(text) => text,
+            Err(e) => return HttpResponse::NotFound().body(e.to_string()),
+        },
+        None => templates::load_template(templates::DEFAULT_TEMPLATE).unwrap_or_default(),
+    };
+    let fields: HashMap<String, String> = body
+        .fields
+        .into_iter()
+        .map(|(k, v)| (k.to_lowercase(), v))
+        .collect();
+    let missing: Vec<String> = templates::template_fields(&text)
+        .into_iter()
+        .filter(|f| !fields.contains_key(f))
+        .collect();
+    if !missing.is_empty() {
+        return HttpResponse::BadRequest()
+            .body(format!("Missing template fields: {}", missing.join(", ")));
+    }
+    match notes::create_note(
+        &body.title,
+        body.template.as_deref(),
+        &fields,
+        None,
+        body.on_conflict,
+    ) {
+        Ok((path, created)) => {
+            let json = serde_json::json!({
+                "slug": path.file_stem().map(|s| s.to_string_lossy()),
+                "path": path
+                    .strip_prefix(notes::notes_dir())
+                    .unwrap_or(&path)
+                    .to_string_lossy(),
+                "created": created,
+            });
+            if created {
+                HttpResponse::Created().json(json)
+            } else {
+                HttpResponse::Ok().json(json)
+            }
+        }
+        Err(e @ NoteError::EmptyTitle) => HttpResponse::BadRequest().body(e.to_string()),
+        Err(e @ NoteError::Exists(_)) => HttpResponse::Conflict().body(e.to_string()),
+        Err(e @ NoteError::TemplateNotFound(_)) => HttpResponse::NotFound().body(e.to_string()),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Failed to create note: {}", e)),
+    }
+}
+
+/// List note templates and the custom fields each asks for.
+pub async fn templates_api() -> impl Responder {
+    HttpResponse::Ok().json(templates::template_infos())
+}
+
+/// Return the periodic note for a date (`YYYY-MM-DD` or `today`), creating
+/// it if needed.
+fn periodic_response(period: Period, date: &str) -> HttpResponse {
+    let date = match periodic::parse_date(date) {
+        Ok(date) => date,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    match periodic::open_periodic(period, date) {
+        Ok((path, created)) => {
+            let slug = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            match extract_note_data_at(&slug, &path) {
+                Some(note) if created => HttpResponse::Created().json(note),
+                Some(note) => HttpResponse::Ok().json(note),
+                None => HttpResponse::InternalServerError().body("Failed to extract note data"),
+            }
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!(
+            "Failed to open {} note: {}",
+            period.label(),
+            e
+        )),
+    }
+}
+
+/// Return or create the daily note for a date.
+pub async fn daily_api(date: web::Path<String>) -> impl Responder {
+    periodic_response(Period::Daily, &date)
+}
+
+/// Return or create the weekly note for the week of a date.
+pub async fn weekly_api(date: web::Path<String>) -> impl Responder {
+    periodic_response(Period::Weekly, &date)
+}
+
+/// Return or create the monthly note for the month of a date.
+pub async fn monthly_api(date: web::Path<String>) -> impl Responder {
+    periodic_response(Period::Monthly, &date)
+}
+
 /// Return details of a specific note as JSON.
 pub async fn note_detail_api(stem: web::Path<String>) -> impl Responder {
-    let p = note_path(&stem);
+    let p = notes::find_note_path(&stem);
     if !p.exists() {
         return HttpResponse::NotFound().body("Note not found");
     }
 
-    match extract_note_data(&stem) {
+    match extract_note_data_at(&stem, &p) {
         Some(note) => HttpResponse::Ok().json(note),
         None => HttpResponse::InternalServerError().body("Failed to extract note data"),
     }
 }
 
-/// Extract note data from a file.
-fn extract_note_data(slug: &str) -> Option<NoteData> {
-    let p = note_path(slug);
-    let metadata = fs::metadata(&p).ok()?;
-    let content = fs::read_to_string(&p).ok()?;
+/// Query parameters for `GET /api/notes/{stem}/related`.
+#[derive(Debug, Deserialize)]
+pub struct RelatedParams {
+    pub limit: Option<usize>,
+}
 
-    let modified = metadata.modified().ok()?;
-    let last_modified = modified
-        .duration_since(std::time::UNIX_EPOCH)
-        .ok()?
-        .as_secs();
+/// Return notes related to a note, with per-reason scores.
+pub async fn related_notes_api(
+    stem: web::Path<String>,
+    params: web::Query<RelatedParams>,
+) -> impl Responder {
+    if !notes::find_note_path(&stem).exists() {
+        return HttpResponse::NotFound().body("Note not found");
+    }
+    let index = VectorIndex::load_or_build(semantic::default_embedder().as_ref()).ok();
+    let related = graph::related_notes(
+        &graph::scan_vault(),
+        index.as_ref(),
+        &stem,
+        params.limit.unwrap_or(10),
+    );
+    HttpResponse::Ok().json(related)
+}
 
-    let yaml_delim = Regex::new(r"^---\s*$").unwrap();
-    let re_title = Regex::new(r"^\s*title:\s*(.+)$").unwrap();
-    let re_tags = Regex::new(r"^\s*tags:\s*\[([^\]]*)\]").unwrap();
-
-    let mut lines = content.lines();
-    let mut title = slug.replace('_', " ");
-    let mut tags = Vec::new();
-    let mut in_yaml = false;
-
-    // Parse YAML front matter
-    for line in lines.by_ref() {
-        if yaml_delim.is_match(line) {
-            if !in_yaml {
-                in_yaml = true;
-                continue;
-            } else {
-                break; // End of front matter
-            }
-        }
+/// Return the links pointing to a note from other notes.
+pub async fn backlinks_api(stem: web::Path<String>) -> impl Responder {
+    match notes::resolve_note(&stem) {
+        Some(path) => HttpResponse::Ok().json(links::backlinks(&path)),
+        None => HttpResponse::NotFound().body("Note not found"),
+    }
+}
 
-        if in_yaml {
-            if let Some(caps) = re_title.captures(line) {
-                if let Some(m) = caps.get(1) {
-                    title = m.as_str().trim().to_string();
-                }
+/// Run an AI task (`summarize` or `keywords`) on a note with the configured
+/// provider.
+pub async fn ai_task_api(path: web::Path<(String, String)>) -> impl Responder {
+    let (task_name, stem) = path.into_inner();
+    let (task, content) = match ai_request(&task_name, &stem) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let result = web::block(move || {
+        let provider = ai::provider();
+        let output = provider
+            .complete(task, &content, &AtomicBool::new(false))
+            .map_err(|e| e.to_string());
+        (provider.name().to_string(), provider.model(task), output)
+    })
+    .await;
+    match result {
+        Ok((provider, model, Ok(output))) => HttpResponse::Ok().json(serde_json::json!({
+            "task": task.name(),
+            "slug": stem,
+            "provider": provider,
+            "model": model,
+            "output": output,
+        })),
+        Ok((_, _, Err(e))) => HttpResponse::BadGateway().body(e),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// The task and note content for an AI endpoint, or the error response for
+/// an unknown task or note.
+fn ai_request(task_name: &str, stem: &str) -> Result<(AiTask, String), HttpResponse> {
+    let task = AiTask::from_name(task_name).ok_or_else(|| {
+        HttpResponse::BadRequest().body(format!("Unknown AI task: {}", task_name))
+    })?;
+    let note =
+        notes::resolve_note(stem).ok_or_else(|| HttpResponse::NotFound().body("Note not found"))?;
+    let content = fs::read_to_string(&note)
+        .map_err(|e| HttpResponse::InternalServerError().body(e.to_string()))?;
+    Ok((task, content))
+}
+
+/// A server-sent event with JSON data.
+fn sse_event(name: &str, data: &serde_json::Value) -> web::Bytes {
+    web::Bytes::from(format!("event: {}\ndata: {}\n\n", name, data))
+}
+
+/// Stream an AI task's answer as server-sent events: `chunk` events with
+/// JSON-encoded pieces of text as they arrive, then a `done` event with the
+/// provider and model, or an `error` event. The request is cancelled when
+/// the client disconnects.
+pub async fn ai_stream_api(path: web::Path<(String, String)>) -> impl Responder {
+    let (task_name, stem) = path.into_inner();
+    let (task, content) = match ai_request(&task_name, &stem) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let provider = ai::provider();
+        let cancel = AtomicBool::new(false);
+        let mut on_chunk = |text: &str| {
+            if tx
+                .unbounded_send(sse_event("chunk", &serde_json::json!(text)))
+                .is_err()
+            {
+                cancel.store(true, Ordering::Relaxed);
             }
+        };
+        let event = match provider.stream(task, &content, &cancel, &mut on_chunk) {
+            Ok(_) => sse_event(
+                "done",
+                &serde_json::json!({
+                    "provider": provider.name(),
+                    "model": provider.model(task),
+                }),
+            ),
+            Err(e) => sse_event("error", &serde_json::json!(e.to_string())),
+        };
+        let _ = tx.unbounded_send(event);
+    });
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(rx.map(Ok::<_, actix_web::Error>))
+}
+
+/// Search modes accepted by `GET /api/search`.
+#[derive(Debug, Default, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiSearchMode {
+    /// The saved-search query language (`tag:x path:y text`).
+    #[default]
+    Query,
+    Fuzzy,
+    Content,
+    Regex,
+    Semantic,
+}
+
+/// Query parameters for `GET /api/search`.
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    #[serde(default)]
+    pub mode: ApiSearchMode,
+    pub limit: Option<usize>,
+}
+
+/// A note returned by the search API, with its score for ranked modes.
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub note: NoteData,
+    pub score: Option<f64>,
+}
 
-            if let Some(caps) = re_tags.captures(line) {
-                if let Some(m) = caps.get(1) {
-                    tags = m
-                        .as_str()
-                        .split(',')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect();
+/// Search notes. Semantic results are ranked by cosine similarity.
+pub async fn search_api(params: web::Query<SearchParams>) -> impl Responder {
+    let limit = params.limit.unwrap_or(50);
+    let ranked: Vec<(String, Option<f64>)> = match params.mode {
+        ApiSearchMode::Query => run_query(&Query::parse(&params.q))
+            .into_iter()
+            .map(|s| (s, None))
+            .collect(),
+        ApiSearchMode::Fuzzy => search::fuzzy_rank(&search::load_search_entries(), &params.q)
+            .into_iter()
+            .map(|r| (r.stem, Some(r.score as f64)))
+            .collect(),
+        ApiSearchMode::Content => search::content_search(
+            &load_notes_list(),
+            &search::note_contents(&notes::note_paths()),
+            &params.q,
+        )
+        .into_iter()
+        .map(|s| (s, None))
+        .collect(),
+        ApiSearchMode::Regex => match search::build_regex(&params.q, search::CaseMode::Smart) {
+            Ok(re) => search::regex_search(
+                &load_notes_list(),
+                &search::note_contents(&notes::note_paths()),
+                &re,
+            )
+            .into_iter()
+            .map(|s| (s, None))
+            .collect(),
+            Err(e) => return HttpResponse::BadRequest().body(format!("Invalid regex: {}", e)),
+        },
+        ApiSearchMode::Semantic => {
+            let embedder = semantic::default_embedder();
+            match VectorIndex::load_or_build(embedder.as_ref()) {
+                Ok(index) => index
+                    .search(embedder.as_ref(), &params.q, limit)
+                    .into_iter()
+                    .map(|(s, score)| (s, Some(score as f64)))
+                    .collect(),
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .body(format!("Failed to build semantic index: {}", e))
                 }
             }
         }
+    };
+
+    let paths = notes::note_paths();
+    let hits: Vec<SearchHit> = ranked
+        .into_iter()
+        .take(limit)
+        .filter_map(|(stem, score)| {
+            let note = extract_note_data_at(&stem, paths.get(&stem)?)?;
+            Some(SearchHit { note, score })
+        })
+        .collect();
+    HttpResponse::Ok().json(hits)
+}
+
+/// Return all tags with note counts and the notes carrying them.
+pub async fn tags_api() -> impl Responder {
+    HttpResponse::Ok().json(tags::tag_counts(&graph::scan_vault()))
+}
+
+/// Body of `POST /api/tags/rename`.
+#[derive(Debug, Deserialize)]
+pub struct RenameTagBody {
+    pub from: String,
+    pub to: String,
+}
+
+/// Body of `POST /api/tags/merge`.
+#[derive(Debug, Deserialize)]
+pub struct MergeTagsBody {
+    pub sources: Vec<String>,
+    pub into: String,
+}
+
+/// Body of `POST /api/tags/add` and `POST /api/tags/remove`.
+#[derive(Debug, Deserialize)]
+pub struct RetagBody {
+    pub tag: String,
+    pub notes: Vec<String>,
+}
+
+/// Apply a tag operation and report the notes that changed.
+fn tag_op_response(op: tags::TagOp) -> HttpResponse {
+    match tags::apply_tag_op(&op) {
+        Ok(changed) => HttpResponse::Ok().json(serde_json::json!({ "changed": changed })),
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+            HttpResponse::BadRequest().body(e.to_string())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            HttpResponse::NotFound().body(e.to_string())
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Failed to update tags: {}", e)),
     }
+}
+
+/// Rename a tag, including nested tags, in every note.
+pub async fn rename_tag_api(body: web::Json<RenameTagBody>) -> impl Responder {
+    let body = body.into_inner();
+    tag_op_response(tags::TagOp::Rename {
+        from: body.from,
+        to: body.to,
+    })
+}
+
+/// Merge several tags into one in every note.
+pub async fn merge_tags_api(body: web::Json<MergeTagsBody>) -> impl Responder {
+    let body = body.into_inner();
+    if body.sources.is_empty() {
+        return HttpResponse::BadRequest().body("No tags to merge");
+    }
+    tag_op_response(tags::TagOp::Merge {
+        sources: body.sources,
+        into: body.into,
+    })
+}
+
+/// Add a tag to the given notes.
+pub async fn add_tag_api(body: web::Json<RetagBody>) -> impl Responder {
+    let body = body.into_inner();
+    tag_op_response(tags::TagOp::Add {
+        tag: body.tag,
+        notes: body.notes,
+    })
+}
+
+/// Remove a tag from the given notes.
+pub async fn remove_tag_api(body: web::Json<RetagBody>) -> impl Responder {
+    let body = body.into_inner();
+    tag_op_response(tags::TagOp::Remove {
+        tag: body.tag,
+        notes: body.notes,
+    })
+}
+
+/// Return the saved searches from the vault config.
+pub async fn saved_searches_api() -> impl Responder {
+    HttpResponse::Ok().json(load_config().saved_searches)
+}
+
+/// Run a saved search and return the matching notes.
+pub async fn saved_search_results_api(name: web::Path<String>) -> impl Responder {
+    let cfg = load_config();
+    let Some(saved) = cfg.saved_search(&name) else {
+        return HttpResponse::NotFound().body("Saved search not found");
+    };
+    let paths = notes::note_paths();
+    let notes: Vec<NoteData> = run_query(&Query::parse(&saved.query))
+        .iter()
+        .filter_map(|stem| extract_note_data_at(stem, paths.get(stem)?))
+        .collect();
+    HttpResponse::Ok().json(notes)
+}
+
+/// Extract note data from a file.
+pub fn extract_note_data_at(slug: &str, p: &Path) -> Option<NoteData> {
+    let metadata = fs::metadata(p).ok()?;
+    let content = fs::read_to_string(p).ok()?;
+
+    let modified = metadata.modified().ok()?;
+    let last_modified = modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+
+    let yaml_delim = Regex::new(r"^---\s*$").unwrap();
+    let title = parse_front_matter(&content)
+        .title
+        .unwrap_or_else(|| slug.replace('_', " "));
+    let tags = tags::extract_tags(&content);
 
     // Extract preview (first few lines after front matter)
     let preview_lines: Vec<&str> = content
@@ -170,22 +581,17 @@ fn extract_note_data(slug: &str) -> Option<NoteData> {
 fn collect_notes_data() -> Vec<NoteData> {
     let mut notes_data = Vec::new();
 
-    for entry in WalkDir::new(notes_dir()).into_iter().filter_map(Result::ok) {
-        if entry.file_type().is_file() {
-            let p = entry.path();
-            if p.extension().and_then(|s| s.to_str()) == Some("md") {
-                if let Some(stem) = p.file_stem() {
-                    let slug = stem.to_string_lossy().to_string();
-                    if let Some(note_data) = extract_note_data(&slug) {
-                        notes_data.push(note_data);
-                    }
-                }
+    for p in walk_notes() {
+        if let Some(stem) = p.file_stem() {
+            let slug = stem.to_string_lossy().to_string();
+            if let Some(note_data) = extract_note_data_at(&slug, &p) {
+                notes_data.push(note_data);
             }
         }
     }
 
     // Sort by last modified (newest first)
-    notes_data.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    notes_data.sort_by_key(|n| std::cmp::Reverse(n.last_modified));
 
     notes_data
 }
@@ -194,10 +600,6 @@ fn collect_notes_data() -> Vec<NoteData> {
 pub async fn index_page() -> impl Responder {
     match fs::read_to_string("static/web/index.html") {
         Ok(mut content) => {
-            // Fix asset paths for proper serving from our handler
-            content = content.replace("src=\"/assets/", "src=\"/assets/");
-            content = content.replace("href=\"/assets/", "href=\"/assets/");
-
             // Make sure crossorigin attributes are removed if present
             content = content.replace(" crossorigin", "");
 
@@ -235,7 +637,7 @@ pub async fn index_page() -> impl Responder {
 
 /// Serve a note file.
 pub async fn serve_note(stem: web::Path<String>) -> impl Responder {
-    let p = note_path(&stem);
+    let p = notes::find_note_path(&stem);
     if !p.exists() {
         return HttpResponse::NotFound().body("Note not found");
     }
@@ -243,9 +645,12 @@ pub async fn serve_note(stem: web::Path<String>) -> impl Responder {
     // Read the file content and convert it to HTML for display
     match fs::read_to_string(&p) {
         Ok(content) => {
-            // Simple conversion: just replace newlines with <br> for now
-            // In a real app, you'd use a proper Markdown to HTML converter
-            let html_content = content.replace('\n', "<br>").replace("  ", "&nbsp;&nbsp;");
+            let html_content = render::render_html(&content, &p);
+            let title = render::html_escape(
+                &parse_front_matter(&content)
+                    .title
+                    .unwrap_or_else(|| stem.replace('_', " ")),
+            );
 
             let html = format!(
                 r#"<!DOCTYPE html>
@@ -288,6 +693,14 @@ pub async fn serve_note(stem: web::Path<String>) -> impl Responder {
     a:hover {{
       text-decoration: underline;
     }}
+    a.broken {{
+      color: #c0392b;
+    }}
+    .embed {{
+      margin: 1em 0;
+      padding: 0 1em;
+      border-left: 3px solid var(--border-color);
+    }}
     .header {{
       display: flex;
       justify-content: space-between;
@@ -310,9 +723,9 @@ pub async fn serve_note(stem: web::Path<String>) -> impl Responder {
   </div>
 </body>
 </html>"#,
-                stem.replace('_', " "), // title
-                stem.replace('_', " "), // h1
-                html_content            // content
+                title,        // title
+                title,        // h1
+                html_content  // content
             );
 
             HttpResponse::Ok()
@@ -323,14 +736,129 @@ pub async fn serve_note(stem: web::Path<String>) -> impl Responder {
     }
 }
 
+/// Query parameters for `GET /api/graph-data`.
+#[derive(Debug, Deserialize)]
+pub struct GraphParams {
+    pub format: Option<GraphFormat>,
+    /// Note to center the sub-graph on.
+    pub focus: Option<String>,
+    /// Neighborhood depth around `focus` (default 1).
+    pub depth: Option<usize>,
+    /// Comma-separated tags; keep notes carrying any of them.
+    pub include_tags: Option<String>,
+    /// Comma-separated tags; drop notes carrying any of them.
+    pub exclude_tags: Option<String>,
+    #[serde(default)]
+    pub hide_tags: bool,
+    #[serde(default)]
+    pub hide_orphans: bool,
+    /// Unix timestamp, or an age like `12h`, `7d` or `2w`.
+    pub since: Option<String>,
+    /// Comma-separated link kinds to keep, e.g. `wikilink,markdown`.
+    pub kinds: Option<String>,
+    /// Add PageRank, betweenness and community to every node.
+    #[serde(default)]
+    pub centrality: bool,
+}
+
+impl GraphParams {
+    /// Build the graph filter, or describe the invalid parameter.
+    fn filter(&self) -> Result<graph::GraphFilter, String> {
+        let tag_list = |s: &Option<String>| -> Vec<String> {
+            s.iter()
+                .flat_map(|s| s.split(','))
+                .map(tags::normalize_tag)
+                .filter(|t| !t.is_empty())
+                .collect()
+        };
+        let since = match self.since.as_deref() {
+            None | Some("") => None,
+            Some(s) => match s.parse::<u64>() {
+                Ok(ts) => Some(ts),
+                Err(_) => {
+                    let age =
+                        search::parse_age(s).ok_or_else(|| format!("Invalid since: {}", s))?;
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0);
+                    Some(now.saturating_sub(age.as_secs()))
+                }
+            },
+        };
+        let kinds = self
+            .kinds
+            .iter()
+            .flat_map(|s| s.split(','))
+            .filter(|k| !k.trim().is_empty())
+            .map(|k| {
+                graph::LinkKind::from_label(k.trim()).ok_or_else(|| format!("Invalid kind: {}", k))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(graph::GraphFilter {
+            focus: self.focus.clone().filter(|f| !f.is_empty()),
+            depth: self.depth.unwrap_or(1),
+            include_tags: tag_list(&self.include_tags),
+            exclude_tags: tag_list(&self.exclude_tags),
+            hide_tags: self.hide_tags,
+            hide_orphans: self.hide_orphans,
+            since,
+            kinds,
+        })
+    }
+}
+
+/// Return centrality metrics and communities for the (optionally filtered)
+/// graph. Accepts the same filter parameters as `GET /api/graph-data`.
+pub async fn graph_analytics_api(params: web::Query<GraphParams>) -> impl Responder {
+    let filter = match params.filter() {
+        Ok(filter) => filter,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let notes = graph::scan_vault();
+    let (nodes, links) = graph::graph_from_notes(&notes);
+    let (nodes, links) = graph::filter_graph(nodes, links, &notes, &filter);
+    HttpResponse::Ok().json(analytics::analyze(&nodes, &links))
+}
+
+/// Query parameters for `GET /api/graph/path`.
+#[derive(Debug, Deserialize)]
+pub struct PathParams {
+    pub from: String,
+    pub to: String,
+    /// Also step through shared tags (default true).
+    pub via_tags: Option<bool>,
+}
+
+/// Return the shortest chain of links between two notes, or `null` if they
+/// are not connected.
+pub async fn graph_path_api(params: web::Query<PathParams>) -> impl Responder {
+    let (nodes, links) = graph::build_graph();
+    let mut ends = Vec::new();
+    for stem in [&params.from, &params.to] {
+        match graph::note_node_id(&nodes, stem) {
+            Some(id) => ends.push(id),
+            None => return HttpResponse::NotFound().body(format!("Note not found: {}", stem)),
+        }
+    }
+    let path = analytics::shortest_path(
+        &nodes,
+        &links,
+        &ends[0],
+        &ends[1],
+        params.via_tags.unwrap_or(true),
+    );
+    HttpResponse::Ok().json(serde_json::json!({
+        "from": params.from,
+        "to": params.to,
+        "path": path,
+    }))
+}
+
 /// Serve the graph viewer HTML.
 pub async fn graph_page() -> impl Responder {
     // Try the new React-based graph view first
     if let Ok(mut content) = fs::read_to_string("static/web/graph.html") {
-        // Fix asset paths for proper serving from our handler
-        content = content.replace("src=\"/assets/", "src=\"/assets/");
-        content = content.replace("href=\"/assets/", "href=\"/assets/");
-
         // Make sure crossorigin attributes are removed if present
         content = content.replace(" crossorigin", "");
 
@@ -360,10 +888,26 @@ pub async fn graph_page() -> impl Responder {
         .body("Redirecting to home page")
 }
 
-/// Return graph data (nodes and links) as JSON.
-pub async fn graph_data() -> impl Responder {
-    let (nodes, links) = build_graph();
-    HttpResponse::Ok().json(serde_json::json!({ "nodes": nodes, "links": links }))
+/// Return graph data (nodes and links) as JSON, or as DOT, GraphML or GEXF
+/// with `?format=`. Filter parameters narrow it to a sub-graph.
+pub async fn graph_data(params: web::Query<GraphParams>) -> impl Responder {
+    let format = params.format.unwrap_or_default();
+    let mut filter = match params.filter() {
+        Ok(filter) => filter,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let notes = graph::scan_vault();
+    let (nodes, links) = graph::graph_from_notes(&notes);
+    if let Some(focus) = &filter.focus {
+        match graph::note_node_id(&nodes, focus) {
+            Some(id) => filter.focus = Some(id),
+            None => return HttpResponse::NotFound().body("Focus note not found"),
+        }
+    }
+    let (nodes, links) = graph::filter_graph(nodes, links, &notes, &filter);
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .body(ExportGraph::from_graph(nodes, links, &notes, params.centrality).render(format))
 }
 
 /// Serve static assets with proper MIME types
@@ -406,116 +950,3 @@ pub async fn serve_assets(path: web::Path<String>) -> impl Responder {
         }
     }
 }
-
-/// Build the graph from note content.
-pub fn build_graph() -> (Vec<NoteNode>, Vec<NoteLink>) {
-    let re_links = Regex::new(r"\[\[(.+?)\]\]").unwrap();
-    let yaml_delim = Regex::new(r"^---\s*$").unwrap();
-    let re_tags = Regex::new(r"^\s*tags:\s*\[([^\]]*)\]").unwrap();
-
-    let mut adjacency = Vec::new(); // (this_stem, [linked_stems])
-    let mut note_tags = Vec::new(); // (this_stem, [tags])
-
-    for entry in WalkDir::new(notes_dir()).into_iter().filter_map(Result::ok) {
-        if entry.file_type().is_file() {
-            let p = entry.path();
-            if p.extension().and_then(|s| s.to_str()) == Some("md") {
-                let stem = p.file_stem().unwrap().to_string_lossy().to_string();
-                let content = fs::read_to_string(p).unwrap_or_default();
-
-                // Find [[links]] in content.
-                let mut found_links = Vec::new();
-                for caps in re_links.captures_iter(&content) {
-                    if let Some(m) = caps.get(1) {
-                        let c = m.as_str().to_lowercase().replace(' ', "_");
-                        found_links.push(c);
-                    }
-                }
-
-                // Parse YAML front matter for tags.
-                let lines = content.lines();
-                let mut in_yaml = false;
-                let mut tags = Vec::new();
-                let mut fm_lines = Vec::new();
-                for line in lines {
-                    if yaml_delim.is_match(line) {
-                        if !in_yaml {
-                            in_yaml = true;
-                            continue;
-                        } else {
-                            break;
-                        }
-                    }
-                    if in_yaml {
-                        fm_lines.push(line);
-                    }
-                }
-                for l in fm_lines {
-                    if let Some(caps) = re_tags.captures(l) {
-                        let inside = caps.get(1).unwrap().as_str();
-                        let raw_tags: Vec<&str> = inside.split(',').map(|s| s.trim()).collect();
-                        for t in raw_tags {
-                            if !t.is_empty() {
-                                tags.push(t.to_lowercase().replace(' ', "_"));
-                            }
-                        }
-                    }
-                }
-
-                adjacency.push((stem.clone(), found_links));
-                note_tags.push((stem, tags));
-            }
-        }
-    }
-
-    // Build sets of nodes and links.
-    let mut note_set = std::collections::HashSet::new();
-    let mut tag_set = std::collections::HashSet::new();
-    let mut links = Vec::new();
-
-    for (src, link_list) in &adjacency {
-        note_set.insert(src.clone());
-        for l in link_list {
-            note_set.insert(l.clone());
-        }
-    }
-    for (st, tags) in &note_tags {
-        note_set.insert(st.clone());
-        for t in tags {
-            tag_set.insert(t.clone());
-        }
-    }
-
-    let mut nodes = Vec::new();
-    for n in note_set {
-        nodes.push(NoteNode {
-            id: n,
-            is_tag: false,
-        });
-    }
-    for t in tag_set {
-        nodes.push(NoteNode {
-            id: t,
-            is_tag: true,
-        });
-    }
-
-    for (src, link_list) in adjacency {
-        for l in link_list {
-            links.push(NoteLink {
-                source: src.clone(),
-                target: l,
-            });
-        }
-    }
-    for (st, tags) in note_tags {
-        for t in tags {
-            links.push(NoteLink {
-                source: st.clone(),
-                target: t,
-            });
-        }
-    }
-
-    (nodes, links)
-}
@@ -0,0 +1,83 @@
+import { useEffect, useRef, useState } from 'react';
+import { Loader2, Sparkles, X } from 'lucide-react';
+
+type AiTask = 'summarize' | 'keywords';
+
+interface AiPanelProps {
+  slug: string;
+}
+
+const AiPanel = ({ slug }: AiPanelProps) => {
+  const [task, setTask] = useState<AiTask | null>(null);
+  const [output, setOutput] = useState('');
+  const [running, setRunning] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const sourceRef = useRef<EventSource | null>(null);
+
+  const stop = () => {
+    sourceRef.current?.close();
+    sourceRef.current = null;
+    setRunning(false);
+  };
+
+  // Close the stream when leaving the note, which cancels the request
+  useEffect(() => stop, [slug]);
+
+  const run = (next: AiTask) => {
+    stop();
+    setTask(next);
+    setOutput('');
+    setError(null);
+    setRunning(true);
+
+    const source = new EventSource(`/api/ai/${next}/${encodeURIComponent(slug)}/stream`);
+    sourceRef.current = source;
+    source.addEventListener('chunk', (event) => {
+      setOutput((prev) => prev + JSON.parse((event as MessageEvent).data));
+    });
+    source.addEventListener('done', stop);
+    source.addEventListener('error', (event) => {
+      const data = (event as MessageEvent).data;
+      setError(data ? JSON.parse(data) : 'The AI request failed');
+      stop();
+    });
+  };
+
+  return (
+    <div className="mt-6 bg-white dark:bg-gray-800 p-4 rounded-lg shadow-sm">
+      <div className="flex items-center gap-2">
+        <Sparkles size={16} className="text-primary-500" />
+        <span className="font-semibold text-gray-800 dark:text-gray-100 mr-auto">AI</span>
+        {(['summarize', 'keywords'] as AiTask[]).map((name) => (
+          <button
+            key={name}
+            className="px-3 py-1 text-sm rounded-md bg-gray-100 dark:bg-gray-700 hover:bg-primary-100 dark:hover:bg-primary-900 disabled:opacity-50"
+            disabled={running}
+            onClick={() => run(name)}
+          >
+            {name === 'summarize' ? 'Summarize' : 'Keywords'}
+          </button>
+        ))}
+        {running && (
+          <button
+            className="inline-flex items-center gap-1 px-3 py-1 text-sm text-gray-500 hover:text-red-600"
+            onClick={stop}
+          >
+            <X size={14} />
+            <span>Cancel</span>
+          </button>
+        )}
+      </div>
+
+      {task && (
+        <div className="mt-3 text-sm text-gray-700 dark:text-gray-300 whitespace-pre-wrap">
+          {output}
+          {running && <Loader2 size={14} className="inline ml-1 animate-spin text-gray-400" />}
+          {error && <p className="text-red-500">{error}</p>}
+        </div>
+      )}
+    </div>
+  );
+};
+
+export default AiPanel;
@@ -98,9 +98,25 @@ const MarkdownRenderer = ({ content }: { content: string }) => {
       );
 
       // Wiki-style links
+      // ([[note]], [[note#Heading]], [[note#^block]], [[note|label]])
       processedMd = processedMd.replace(
-        /\[\[(.*?)\]\]/gim,
-        '<a href="/notes/$1" class="text-primary-500 hover:underline">$1</a>'
+        /\[\[([^\]|#]*)(?:#(\^?)([^\]|]*))?(?:\|([^\]]*))?\]\]/gim,
+        (_match, target: string, caret: string, anchor: string, label: string) => {
+          let fragment = "";
+          if (anchor) {
+            fragment = caret
+              ? `#^${anchor.trim()}`
+              : "#" +
+                anchor
+                  .trim()
+                  .toLowerCase()
+                  .replace(/ /g, "-")
+                  .replace(/[^\p{L}\p{N}_-]/gu, "");
+          }
+          const text = label || target + (anchor ? `#${caret}${anchor}` : "");
+          const href = target.trim() ? `/notes/${target.trim()}${fragment}` : fragment;
+          return `<a href="${href}" class="text-primary-500 hover:underline">${text}</a>`;
+        }
       );
 
       // Code blocks
@@ -1,5 +1,5 @@
 import { useState, useEffect, useRef } from "react";
-import { useNavigate } from "react-router-dom";
+import { useLocation, useNavigate } from "react-router-dom";
 import { ZoomIn, ZoomOut, RotateCcw, Network } from "lucide-react";
 import { motion } from "framer-motion";
 import { GraphData, Node, Link } from "../types";
@@ -19,13 +19,15 @@ const GraphPage = () => {
   const [width, setWidth] = useState(800);
   const [height, setHeight] = useState(600);
   const navigate = useNavigate();
+  const location = useLocation();
 
   // Fetch graph data
   useEffect(() => {
     const fetchGraphData = async () => {
       try {
         setLoading(true);
-        const response = await fetch("/api/graph-data");
+        // Pass ?focus=, depth, include_tags, ... through to request a sub-graph
+        const response = await fetch(`/api/graph-data${location.search}`);
 
         if (!response.ok) {
           throw new Error(
@@ -45,7 +47,7 @@ const GraphPage = () => {
     };
 
     fetchGraphData();
-  }, []);
+  }, [location.search]);
 
   // Update dimensions when window resizes
   useEffect(() => {
@@ -132,11 +134,24 @@ const GraphPage = () => {
       .data(graphData.links)
       .enter()
       .append("path")
-      .attr("class", "link")
+      .attr("class", (d) => `link link-${d.type}`)
       .attr("stroke", linkColor)
-      .attr("stroke-width", 1.5)
+      // Repeated links are drawn thicker; tag and relation edges are dashed
+      .attr("stroke-width", (d) => 1.5 + Math.min(d.weight - 1, 4))
+      .attr("stroke-dasharray", (d) =>
+        d.type === "tag" || d.type === "nested_tag"
+          ? "4 3"
+          : d.type === "relation"
+            ? "1 3"
+            : null,
+      )
       .attr("fill", "none");
 
+    link
+      .filter((d) => !!d.context)
+      .append("title")
+      .text((d) => d.context ?? "");
+
     // Create nodes
     const node = g
       .selectAll(".node")
@@ -3,6 +3,7 @@ import { Calendar, ExternalLink, Loader2 } from 'lucide-react';
 import { motion } from 'framer-motion';
 import { useNote } from '../hooks/useNotes';
 import MarkdownRenderer from '../components/MarkdownRenderer';
+import AiPanel from '../components/AiPanel';
 
 const NotePage = () => {
   const { slug } = useParams<{ slug: string }>();
@@ -124,6 +125,8 @@ const NotePage = () => {
         <MarkdownRenderer content={content} />
       </motion.div>
 
+      <AiPanel slug={slug} />
+
       {/* Editor link */}
       <div className="mt-6 text-right">
         <button
@@ -3,7 +3,15 @@ import { SimulationNodeDatum, SimulationLinkDatum } from "d3";
 // Graph visualization types
 export interface Node extends SimulationNodeDatum {
   id: string;
+  title: string;
+  tags: string[];
   is_tag: boolean;
+  exists: boolean;
+  degree: number;
+  mtime: number | null;
+  pagerank?: number;
+  betweenness?: number;
+  community?: number;
   x?: number;
   y?: number;
   fx?: number | null;
@@ -14,6 +22,15 @@ export interface Node extends SimulationNodeDatum {
 export interface Link extends SimulationLinkDatum<Node> {
   source: string | Node;
   target: string | Node;
+  type:
+    | "wikilink"
+    | "markdown"
+    | "embed"
+    | "relation"
+    | "tag"
+    | "nested_tag";
+  weight: number;
+  context: string | null;
 }
 
 export interface GraphData {